@@ -0,0 +1,388 @@
+use crate::{assert_script_error, TestKey};
+use ckb_testtool::ckb_error::Error;
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{Cycle, DepType, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use ckb_testtool::context::Context;
+use ckb_transaction_cobuild::host::{otx_signing_message_hash, signing_message_hash};
+use ckb_transaction_cobuild::schemas::{
+    basic::{
+        Action, ActionVec, Message, Otx, OtxStart, ResolvedInputs, SealPair, SealPairVec,
+        SighashAll, SighashAllOnly,
+    },
+    top_level::{WitnessLayout, WitnessLayoutUnion},
+};
+use ckb_transaction_cobuild::OtxRanges;
+
+const INPUT_CAPACITY: u64 = 1000;
+
+struct InputCell {
+    group: usize,
+    type_: Option<Script>,
+    data: Bytes,
+}
+
+fn new_action(script_hash: Byte32, data: Bytes) -> Action {
+    Action::new_builder()
+        .script_hash(script_hash)
+        .data(data.pack())
+        .build()
+}
+
+fn new_message(actions: &[Action]) -> Message {
+    Message::new_builder()
+        .actions(ActionVec::new_builder().set(actions.to_vec()).build())
+        .build()
+}
+
+fn layout_witness(layout: WitnessLayoutUnion) -> Bytes {
+    WitnessLayout::new_builder().set(layout).build().as_bytes()
+}
+
+///
+/// an otx of the batch, its input cells, output cells and message. The otx
+/// covers no cell deps or header deps.
+///
+#[derive(Default)]
+pub struct OtxBuilder {
+    inputs: Vec<InputCell>,
+    outputs: Vec<(CellOutput, Bytes)>,
+    actions: Vec<Action>,
+}
+
+impl OtxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// an input cell locked by lock group `group`
+    ///
+    pub fn input(self, group: usize, data: Bytes) -> Self {
+        self.typed_input(group, None, data)
+    }
+
+    pub fn typed_input(mut self, group: usize, type_: Option<Script>, data: Bytes) -> Self {
+        self.inputs.push(InputCell { group, type_, data });
+        self
+    }
+
+    pub fn output(mut self, output: CellOutput, data: Bytes) -> Self {
+        self.outputs.push((output, data));
+        self
+    }
+
+    ///
+    /// an action of the otx message
+    ///
+    pub fn action(mut self, script_hash: Byte32, data: Bytes) -> Self {
+        self.actions.push(new_action(script_hash, data));
+        self
+    }
+}
+
+///
+/// build a transaction in cobuild witness layouts and sign it with the test
+/// key of every lock group.
+///
+/// Input and output cells added to the builder are placed before those of
+/// the otx batch. Each lock group with such input cells signs the whole
+/// transaction in the witness of its first input cell: SighashAll carrying the
+/// message for the first one if there are actions, SighashAllOnly otherwise.
+/// The OtxStart witness follows them, then an Otx witness for every otx,
+/// sealed by each lock group with input cells in the otx. Extra witnesses are
+/// appended after all of them.
+///
+pub struct CobuildTxBuilder {
+    context: Context,
+    cell_deps: Vec<CellDep>,
+    header_deps: Vec<Byte32>,
+    groups: Vec<(Script, TestKey)>,
+    inputs: Vec<InputCell>,
+    outputs: Vec<(CellOutput, Bytes)>,
+    actions: Vec<Action>,
+    otxs: Vec<OtxBuilder>,
+    extra_witnesses: Vec<Bytes>,
+}
+
+impl CobuildTxBuilder {
+    ///
+    /// `context` has the scripts deployed, their cell deps are completed by
+    /// `Context::complete_tx` in `build`
+    ///
+    pub fn new(context: Context) -> Self {
+        Self {
+            context,
+            cell_deps: Vec::new(),
+            header_deps: Vec::new(),
+            groups: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            actions: Vec::new(),
+            otxs: Vec::new(),
+            extra_witnesses: Vec::new(),
+        }
+    }
+
+    ///
+    /// e.g. to deploy cells or build scripts before adding them
+    ///
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    pub fn cell_dep(mut self, out_point: OutPoint, dep_type: DepType) -> Self {
+        self.cell_deps.push(
+            CellDep::new_builder()
+                .out_point(out_point)
+                .dep_type(dep_type.into())
+                .build(),
+        );
+        self
+    }
+
+    pub fn header_dep(mut self, header_hash: Byte32) -> Self {
+        self.header_deps.push(header_hash);
+        self
+    }
+
+    ///
+    /// a lock group whose seals are signed by `key`, lock groups are indexed
+    /// in the order they are added
+    ///
+    pub fn lock_group(mut self, lock: Script, key: TestKey) -> Self {
+        self.groups.push((lock, key));
+        self
+    }
+
+    ///
+    /// an input cell locked by lock group `group`
+    ///
+    pub fn input(self, group: usize, data: Bytes) -> Self {
+        self.typed_input(group, None, data)
+    }
+
+    pub fn typed_input(mut self, group: usize, type_: Option<Script>, data: Bytes) -> Self {
+        self.inputs.push(InputCell { group, type_, data });
+        self
+    }
+
+    pub fn output(mut self, output: CellOutput, data: Bytes) -> Self {
+        self.outputs.push((output, data));
+        self
+    }
+
+    ///
+    /// an action of the message in SighashAll
+    ///
+    pub fn action(mut self, script_hash: Byte32, data: Bytes) -> Self {
+        self.actions.push(new_action(script_hash, data));
+        self
+    }
+
+    pub fn otx(mut self, otx: OtxBuilder) -> Self {
+        self.otxs.push(otx);
+        self
+    }
+
+    ///
+    /// a witness beyond the input cells, covered by the signing message hash
+    /// of SighashAll and SighashAllOnly
+    ///
+    pub fn extra_witness(mut self, witness: Bytes) -> Self {
+        self.extra_witnesses.push(witness);
+        self
+    }
+
+    pub fn build(self) -> CobuildTx {
+        let CobuildTxBuilder {
+            mut context,
+            cell_deps,
+            header_deps,
+            groups,
+            inputs,
+            outputs,
+            actions,
+            otxs,
+            extra_witnesses,
+        } = self;
+        assert!(
+            actions.is_empty() || !inputs.is_empty(),
+            "the actions are carried by SighashAll of an input cell out of otx"
+        );
+
+        // input cells
+        let all_inputs = inputs
+            .iter()
+            .chain(otxs.iter().flat_map(|o| o.inputs.iter()));
+        let mut tx_inputs = Vec::new();
+        let mut resolved_outputs = CellOutputVec::new_builder();
+        let mut resolved_outputs_data = BytesVec::new_builder();
+        for input in all_inputs {
+            let cell = CellOutput::new_builder()
+                .capacity(INPUT_CAPACITY.pack())
+                .lock(groups[input.group].0.clone())
+                .type_(input.type_.clone().pack())
+                .build();
+            let out_point = context.create_cell(cell.clone(), input.data.clone());
+            tx_inputs.push(CellInput::new_builder().previous_output(out_point).build());
+            resolved_outputs = resolved_outputs.push(cell);
+            resolved_outputs_data = resolved_outputs_data.push(input.data.pack());
+        }
+        let resolved_inputs = ResolvedInputs::new_builder()
+            .outputs(resolved_outputs.build())
+            .outputs_data(resolved_outputs_data.build())
+            .build();
+
+        // output cells
+        let all_outputs: Vec<_> = outputs
+            .iter()
+            .chain(otxs.iter().flat_map(|o| o.outputs.iter()))
+            .cloned()
+            .collect();
+        let tx = TransactionBuilder::default()
+            .cell_deps(cell_deps)
+            .header_deps(header_deps)
+            .inputs(tx_inputs)
+            .outputs(all_outputs.iter().map(|(output, _)| output.clone()))
+            .outputs_data(all_outputs.iter().map(|(_, data)| data.pack()))
+            .build();
+        let tx = context.complete_tx(tx);
+        let inputs_len = tx.inputs().len();
+
+        // the witness index of the seal of every lock group out of otx
+        let mut sighash_seals: Vec<(usize, usize)> = Vec::new();
+        for (i, input) in inputs.iter().enumerate() {
+            if sighash_seals.iter().all(|(_, group)| *group != input.group) {
+                sighash_seals.push((i, input.group));
+            }
+        }
+        let mut witnesses = vec![Bytes::new(); inputs.len()];
+
+        // otx batch
+        if !otxs.is_empty() {
+            let otx_start = OtxStart::new_builder()
+                .start_input_cell((inputs.len() as u32).pack())
+                .start_output_cell((outputs.len() as u32).pack())
+                .build();
+            witnesses.push(layout_witness(WitnessLayoutUnion::OtxStart(otx_start)));
+        }
+        let mut input_cells = inputs.len();
+        let mut output_cells = outputs.len();
+        for otx in &otxs {
+            let ranges = OtxRanges {
+                input_cells: input_cells..input_cells + otx.inputs.len(),
+                output_cells: output_cells..output_cells + otx.outputs.len(),
+                cell_deps: 0..0,
+                header_deps: 0..0,
+            };
+            input_cells = ranges.input_cells.end;
+            output_cells = ranges.output_cells.end;
+            let message = new_message(&otx.actions);
+            let digest =
+                otx_signing_message_hash(&message, &tx.data(), &resolved_inputs, &ranges).unwrap();
+            let mut seal_groups: Vec<usize> = otx.inputs.iter().map(|input| input.group).collect();
+            seal_groups.sort_unstable();
+            seal_groups.dedup();
+            let seals = seal_groups.into_iter().map(|group| {
+                let (lock, key) = &groups[group];
+                SealPair::new_builder()
+                    .script_hash(lock.calc_script_hash())
+                    .seal(key.sign(&digest).pack())
+                    .build()
+            });
+            let otx = Otx::new_builder()
+                .input_cells((otx.inputs.len() as u32).pack())
+                .output_cells((otx.outputs.len() as u32).pack())
+                .message(message)
+                .seals(SealPairVec::new_builder().extend(seals).build())
+                .build();
+            witnesses.push(layout_witness(WitnessLayoutUnion::Otx(otx)));
+        }
+        if witnesses.len() < inputs_len {
+            witnesses.resize(inputs_len, Bytes::new());
+        }
+        witnesses.extend(extra_witnesses);
+
+        // the witnesses of the seals are out of the signing message hash
+        let tx = tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses.iter().map(|w| w.pack()).collect())
+            .build();
+        let message = if actions.is_empty() {
+            None
+        } else {
+            Some(new_message(&actions))
+        };
+        let digest = signing_message_hash(&message, &tx.data(), &resolved_inputs).unwrap();
+        for (n, (i, group)) in sighash_seals.into_iter().enumerate() {
+            let seal = groups[group].1.sign(&digest);
+            witnesses[i] = match (&message, n) {
+                (Some(message), 0) => layout_witness(WitnessLayoutUnion::SighashAll(
+                    SighashAll::new_builder()
+                        .seal(seal.pack())
+                        .message(message.clone())
+                        .build(),
+                )),
+                _ => layout_witness(WitnessLayoutUnion::SighashAllOnly(
+                    SighashAllOnly::new_builder().seal(seal.pack()).build(),
+                )),
+            };
+        }
+        let tx = tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses.iter().map(|w| w.pack()).collect())
+            .build();
+
+        CobuildTx {
+            context,
+            tx,
+            resolved_inputs,
+        }
+    }
+}
+
+///
+/// a signed transaction with the context to verify it
+///
+pub struct CobuildTx {
+    pub context: Context,
+    pub tx: TransactionView,
+    pub resolved_inputs: ResolvedInputs,
+}
+
+impl CobuildTx {
+    pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, Error> {
+        self.context.verify_tx(&self.tx, max_cycles)
+    }
+
+    ///
+    /// verify the transaction with witness `index` replaced by `witness`
+    ///
+    pub fn verify_with_witness(
+        &self,
+        index: usize,
+        witness: Bytes,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, Error> {
+        let mut witnesses: Vec<_> = self.tx.witnesses().into_iter().collect();
+        witnesses[index] = witness.pack();
+        let tx = self
+            .tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build();
+        self.context.verify_tx(&tx, max_cycles)
+    }
+
+    ///
+    /// assert the transaction is rejected by a script exiting with `err_code`
+    ///
+    pub fn assert_error(&self, max_cycles: Cycle, err_code: i8) {
+        let err = self.verify(max_cycles).expect_err("rejected");
+        assert_script_error(err, err_code);
+    }
+}
@@ -0,0 +1,59 @@
+use ckb_testtool::ckb_crypto::secp::{Generator, Message as SecpMessage, Privkey};
+use ckb_testtool::ckb_hash::blake2b_256;
+use ckb_testtool::ckb_types::bytes::Bytes;
+
+///
+/// a secp256k1 key signing the seals of a lock group
+///
+#[derive(Clone)]
+pub struct TestKey {
+    privkey: Privkey,
+    pubkey_hash: [u8; 20],
+}
+
+impl TestKey {
+    pub fn random() -> Self {
+        Self::from_privkey(Generator::random_privkey())
+    }
+
+    pub fn from_privkey(privkey: Privkey) -> Self {
+        let pubkey_hash = blake2b_256(privkey.pubkey().expect("pubkey").serialize().as_slice())
+            [..20]
+            .try_into()
+            .unwrap();
+        Self {
+            privkey,
+            pubkey_hash,
+        }
+    }
+
+    pub fn privkey(&self) -> &Privkey {
+        &self.privkey
+    }
+
+    ///
+    /// blake160 of the compressed public key
+    ///
+    pub fn pubkey_hash(&self) -> [u8; 20] {
+        self.pubkey_hash
+    }
+
+    ///
+    /// the args of a secp256k1_blake160 style lock script, e.g. the lock demo
+    ///
+    pub fn args(&self) -> Bytes {
+        Bytes::from(self.pubkey_hash.to_vec())
+    }
+
+    ///
+    /// the 65 bytes recoverable signature of a signing message hash
+    ///
+    pub fn sign(&self, message_hash: &[u8; 32]) -> Bytes {
+        let signature = self
+            .privkey
+            .sign_recoverable(&SecpMessage::from_slice(message_hash).unwrap())
+            .expect("sign")
+            .serialize();
+        Bytes::from(signature.to_vec())
+    }
+}
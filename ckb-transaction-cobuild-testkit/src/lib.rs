@@ -0,0 +1,28 @@
+//! Helpers for testing scripts which support transaction co-build protocol
+//! with ckb-testtool: a builder signing cobuild transactions with test keys,
+//! the transactions of the demo scripts in `tx`, the loader of built binaries
+//! and assertions of script errors.
+
+use ckb_testtool::ckb_error::Error;
+
+pub mod builder;
+pub mod key;
+pub mod loader;
+pub mod tx;
+
+pub use builder::{CobuildTx, CobuildTxBuilder, OtxBuilder};
+pub use key::TestKey;
+pub use loader::{Loader, TestEnv};
+
+///
+/// assert the transaction is rejected by a script exiting with `err_code`
+///
+pub fn assert_script_error(err: Error, err_code: i8) {
+    let error_string = err.to_string();
+    assert!(
+        error_string.contains(format!("error code {} ", err_code).as_str()),
+        "error_string: {}, expected_error_code: {}",
+        error_string,
+        err_code
+    );
+}
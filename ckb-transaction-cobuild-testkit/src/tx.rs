@@ -1,4 +1,8 @@
-use super::*;
+//! Transactions of the demo lock and type scripts, signed with the witnesses
+//! of every lock group described by `MessageWitnesses`, see `gen_tx` and
+//! `sign_tx`.
+
+use crate::Loader;
 use ckb_testtool::ckb_crypto::secp::{Generator, Message as SecpMessage, Privkey};
 use ckb_testtool::ckb_types::{
     bytes::Bytes,
@@ -7,13 +11,13 @@ use ckb_testtool::ckb_types::{
     prelude::*,
 };
 use ckb_testtool::context::Context;
-use ckb_transaction_cobuild::blake2b::{new_sighash_all_blake2b, new_sighash_all_only_blake2b};
+use ckb_transaction_cobuild::blake2b::new_default_blake2b;
+use ckb_transaction_cobuild::host::signing_message_hash;
 use ckb_transaction_cobuild::schemas::{
     basic::{Action, ActionVec, Message, ResolvedInputs, SighashAll, SighashAllOnly},
     blockchain,
     top_level::{WitnessLayout, WitnessLayoutUnion},
 };
-use molecule::prelude::*;
 use rand::{thread_rng, RngCore};
 
 pub struct MessageData {
@@ -22,6 +26,7 @@ pub struct MessageData {
     pub group_size: usize,
     pub action: Option<Message>,
     pub sign: Option<Vec<u8>>,
+    pub input_data: Bytes,
 
     pub config_failed_pubkey_hash: bool,
 }
@@ -41,6 +46,7 @@ impl MessageData {
             group_size,
             action: None,
             sign: None,
+            input_data: Bytes::new(),
 
             config_failed_pubkey_hash: false,
         }
@@ -58,12 +64,11 @@ impl MessageData {
                     SighashAll::new_builder()
                         .seal(
                             blockchain::Bytes::new_builder()
-                                .set(sign.iter().map(|f| f.clone().into()).collect())
+                                .set(sign.iter().map(|f| (*f).into()).collect())
                                 .build(),
                         )
                         .message(action.clone())
-                        .build()
-                        .into(),
+                        .build(),
                 ))
                 .build(),
             None => WitnessLayout::new_builder()
@@ -71,11 +76,10 @@ impl MessageData {
                     SighashAllOnly::new_builder()
                         .seal(
                             blockchain::Bytes::new_builder()
-                                .set(sign.iter().map(|f| f.clone().into()).collect())
+                                .set(sign.iter().map(|f| (*f).into()).collect())
                                 .build(),
                         )
-                        .build()
-                        .into(),
+                        .build(),
                 ))
                 .build(),
         }
@@ -173,7 +177,7 @@ impl MessageWitnesses {
     pub fn rng_byte32() -> blockchain::Byte32 {
         let mut buf = [0u8; 32];
         thread_rng().fill_bytes(&mut buf);
-        let buf: Vec<Byte> = buf.iter().map(|f| f.clone().into()).collect();
+        let buf: Vec<Byte> = buf.iter().map(|f| (*f).into()).collect();
 
         blockchain::Byte32::new_builder()
             .set(buf.try_into().unwrap())
@@ -181,13 +185,11 @@ impl MessageWitnesses {
     }
 
     pub fn rng_bytes(len: usize) -> blockchain::Bytes {
-        let mut buf = Vec::with_capacity(len);
-        buf.resize(len, 0);
-
+        let mut buf = vec![0u8; len];
         thread_rng().fill_bytes(&mut buf);
 
         blockchain::Bytes::new_builder()
-            .set(buf.iter().map(|f| f.clone().into()).collect())
+            .set(buf.iter().map(|f| (*f).into()).collect())
             .build()
     }
 }
@@ -235,9 +237,9 @@ pub fn gen_tx(witnesses: &MessageWitnesses) -> (TransactionView, ResolvedInputs,
                 .capacity(1000u64.pack())
                 .lock(lock_script.clone())
                 .build();
-            let data = Bytes::new();
+            let data = data.input_data.clone();
             cell_output_vec_builder = cell_output_vec_builder.push(cell.clone());
-            bytes_vec_builder = bytes_vec_builder.push(blockchain::Bytes::new_builder().build());
+            bytes_vec_builder = bytes_vec_builder.push(data.pack());
 
             let input_out_point = context.create_cell(cell, data);
             let input = CellInput::new_builder()
@@ -287,56 +289,11 @@ pub fn gen_tx(witnesses: &MessageWitnesses) -> (TransactionView, ResolvedInputs,
     (tx, resolved_inputs, context)
 }
 
-fn generate_signing_message_hash(
-    message: &Option<Message>,
-    tx: &TransactionView,
-    resolved_inputs: &ResolvedInputs,
-) -> [u8; 32] {
-    // message
-    let mut hasher = match message {
-        Some(m) => {
-            let mut hasher = new_sighash_all_blake2b();
-            hasher.update(m.as_slice());
-            hasher
-        }
-        None => new_sighash_all_only_blake2b(),
-    };
-    // tx hash
-    hasher.update(tx.hash().as_slice());
-    // inputs cell and data
-    let inputs_len = tx.inputs().len();
-    debug_assert!(inputs_len == resolved_inputs.outputs().len());
-    debug_assert!(inputs_len == resolved_inputs.outputs_data().len());
-    for i in 0..inputs_len {
-        let input_cell = resolved_inputs.outputs().get(i).unwrap();
-        hasher.update(&input_cell.as_slice());
-        let input_cell_data = resolved_inputs.outputs_data().get(i).unwrap();
-        hasher.update(&(input_cell_data.len() as u32).to_le_bytes());
-        hasher.update(&input_cell_data.raw_data());
-    }
-    // extra witnesses
-    for witness in tx.witnesses().into_iter().skip(inputs_len) {
-        hasher.update(&(witness.len() as u32).to_le_bytes());
-        hasher.update(&witness.raw_data());
-    }
-
-    let mut result = [0u8; 32];
-    hasher.finalize(&mut result);
-    result
-}
-
 fn witness_is_empty(tx: &TransactionView, index: usize) -> bool {
-    let w = tx.witnesses().get(index);
-    if w.is_none() {
-        return true;
-    }
-
-    let w = w.unwrap();
-    if w.is_empty() || w.len() == 4 {
-        return true;
+    match tx.witnesses().get(index) {
+        Some(w) => w.is_empty() || w.len() == 4,
+        None => true,
     }
-
-    false
 }
 
 pub fn sign_tx(
@@ -345,7 +302,7 @@ pub fn sign_tx(
     resolved_inputs: ResolvedInputs,
 ) -> TransactionView {
     let signing_message_hash =
-        generate_signing_message_hash(&witnesses.get_action(), &tx, &resolved_inputs);
+        signing_message_hash(&witnesses.get_action(), &tx.data(), &resolved_inputs).unwrap();
 
     let mut data_count = 0usize;
     for i in 0..tx.inputs().len() {
@@ -366,8 +323,39 @@ pub fn sign_tx(
         data_count += 1;
     }
 
-    let witnesses = witnesses.get_witnesses();
+    // witnesses appended after `gen_tx`, e.g. beyond the input cells, are kept
+    let mut all_witnesses = witnesses.get_witnesses();
+    let appended: Vec<Bytes> = tx
+        .witnesses()
+        .into_iter()
+        .skip(all_witnesses.len())
+        .map(|w| w.raw_data())
+        .collect();
+    all_witnesses.extend(appended);
     tx.as_advanced_builder()
-        .set_witnesses(witnesses.iter().map(|f| f.pack()).collect())
+        .set_witnesses(all_witnesses.iter().map(|f| f.pack()).collect())
         .build()
 }
+
+// the signing message hash of secp256k1_blake160_sighash_all, the lock field
+// of the first witness in group must be filled by zeros
+pub fn generate_legacy_signing_message_hash(tx: &TransactionView, group: &[usize]) -> [u8; 32] {
+    let mut hasher = new_default_blake2b();
+    // tx hash
+    hasher.update(tx.hash().as_slice());
+    // witnesses of script group
+    for i in group {
+        let witness = tx.witnesses().get(*i).unwrap();
+        hasher.update(&(witness.raw_data().len() as u64).to_le_bytes());
+        hasher.update(&witness.raw_data());
+    }
+    // extra witnesses
+    for witness in tx.witnesses().into_iter().skip(tx.inputs().len()) {
+        hasher.update(&(witness.raw_data().len() as u64).to_le_bytes());
+        hasher.update(&witness.raw_data());
+    }
+
+    let mut result = [0u8; 32];
+    hasher.finalize(&mut result);
+    result
+}
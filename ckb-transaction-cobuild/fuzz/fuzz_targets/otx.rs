@@ -0,0 +1,160 @@
+//! Arbitrary witness lists with otx batches through `fetch_otx_batch`,
+//! `OtxMessageIter` and `cobuild_entry` of every lock group: no panics, the
+//! batch and digests agree with the off-chain helpers, and one digest never
+//! signs two different otxs.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use ckb_transaction_cobuild::{
+    cobuild_entry, fetch_otx_batch,
+    host::{otx_batch, otx_signing_message_hash},
+    native::NativeTransaction,
+    parse_otx_message,
+    schemas::{
+        basic::{Message, ResolvedInputs},
+        blockchain::Transaction,
+        top_level::{WitnessLayoutReader, WitnessLayoutUnionReader},
+    },
+    OtxRanges,
+};
+use ckb_transaction_cobuild_fuzz::{build_tx, input_lock_hashes, lock_hash, FuzzWitness, TxShape};
+use libfuzzer_sys::fuzz_target;
+use molecule::prelude::{Entity, Reader};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    shape: TxShape,
+    witnesses: Vec<FuzzWitness>,
+}
+
+fn otx_message(tx: &Transaction, witness_index: usize) -> Message {
+    let witness = tx.witnesses().get(witness_index).unwrap().raw_data();
+    match WitnessLayoutReader::from_slice(&witness).unwrap().to_enum() {
+        WitnessLayoutUnionReader::Otx(otx) => otx.message().to_entity(),
+        _ => unreachable!(),
+    }
+}
+
+///
+/// the serialized items covered by `ranges`
+///
+fn covered(tx: &Transaction, resolved_inputs: &ResolvedInputs, ranges: &OtxRanges) -> Vec<Vec<u8>> {
+    let raw = tx.raw();
+    let mut items = Vec::new();
+    for i in ranges.input_cells.clone() {
+        items.push(raw.inputs().get(i).unwrap().as_slice().to_vec());
+        items.push(
+            resolved_inputs
+                .outputs()
+                .get(i)
+                .unwrap()
+                .as_slice()
+                .to_vec(),
+        );
+        items.push(
+            resolved_inputs
+                .outputs_data()
+                .get(i)
+                .unwrap()
+                .as_slice()
+                .to_vec(),
+        );
+    }
+    for i in ranges.output_cells.clone() {
+        items.push(raw.outputs().get(i).unwrap().as_slice().to_vec());
+        items.push(raw.outputs_data().get(i).unwrap().as_slice().to_vec());
+    }
+    for i in ranges.cell_deps.clone() {
+        items.push(raw.cell_deps().get(i).unwrap().as_slice().to_vec());
+    }
+    for i in ranges.header_deps.clone() {
+        items.push(raw.header_deps().get(i).unwrap().as_slice().to_vec());
+    }
+    items
+}
+
+fuzz_target!(|input: Input| {
+    let witnesses: Vec<Vec<u8>> = input.witnesses.iter().map(FuzzWitness::to_bytes).collect();
+    let (tx, resolved_inputs) = build_tx(&input.shape, &witnesses);
+    let batch = otx_batch(&tx);
+
+    for script_hash in input_lock_hashes(&input.shape) {
+        let native =
+            NativeTransaction::new(tx.clone(), resolved_inputs.clone()).lock_group(script_hash);
+        // the batch is the same as the off-chain one
+        let fetched = native.clone().run(fetch_otx_batch);
+        let otxs = match (&batch, fetched) {
+            (Ok(otxs), Ok(fetched)) => {
+                let (first, last) = (&otxs[0].1, &otxs[otxs.len() - 1].1);
+                assert_eq!(fetched.start_witness + 1, otxs[0].0);
+                assert_eq!(fetched.otx_count, otxs.len());
+                assert_eq!(
+                    fetched.ranges,
+                    OtxRanges {
+                        input_cells: first.input_cells.start..last.input_cells.end,
+                        output_cells: first.output_cells.start..last.output_cells.end,
+                        cell_deps: first.cell_deps.start..last.cell_deps.end,
+                        header_deps: first.header_deps.start..last.header_deps.end,
+                    }
+                );
+                otxs
+            }
+            (Err(e), Err(fetched)) => {
+                assert_eq!(*e, fetched);
+                let _ = native.run(|| cobuild_entry(|_, _| true));
+                continue;
+            }
+            (batch, fetched) => panic!("{:?} != {:?}", batch, fetched),
+        };
+
+        // the otxs with input cells of the group are yielded with the
+        // off-chain digests, in order
+        let yielded: Vec<[u8; 32]> = native
+            .clone()
+            .run(|| {
+                parse_otx_message(script_hash).and_then(|iter| {
+                    iter.map(|item| item.map(|(digest, _)| digest))
+                        .collect::<Result<_, _>>()
+                })
+            })
+            .unwrap();
+        let expected: Vec<[u8; 32]> = otxs
+            .iter()
+            .filter(|(_, ranges)| {
+                ranges.input_cells.clone().any(|i| {
+                    let lock = input.shape.input_locks.get(i).copied().unwrap_or(0);
+                    lock_hash(lock) == script_hash
+                })
+            })
+            .map(|(i, ranges)| {
+                otx_signing_message_hash(&otx_message(&tx, *i), &tx, &resolved_inputs, ranges)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(yielded, expected);
+
+        let _ = native.run(|| cobuild_entry(|_, _| true));
+    }
+
+    // otxs with the same digest sign the same message and items
+    if let Ok(otxs) = &batch {
+        let signed: Vec<_> = otxs
+            .iter()
+            .map(|(i, ranges)| {
+                let message = otx_message(&tx, *i);
+                let digest =
+                    otx_signing_message_hash(&message, &tx, &resolved_inputs, ranges).unwrap();
+                let items = covered(&tx, &resolved_inputs, ranges);
+                (digest, message.as_slice().to_vec(), items)
+            })
+            .collect();
+        for (k, a) in signed.iter().enumerate() {
+            for b in &signed[k + 1..] {
+                if a.0 == b.0 {
+                    assert_eq!((&a.1, &a.2), (&b.1, &b.2));
+                }
+            }
+        }
+    }
+});
@@ -0,0 +1,76 @@
+//! Two arbitrary witness lists of the same transaction through `parse_message`
+//! of every lock group: no panics, the seal and digest agree with the
+//! off-chain helpers, and one digest never signs two different layouts.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use ckb_transaction_cobuild::{
+    classify_witness, fetch_message, host::signing_message_hash, native::NativeTransaction,
+    parse_message,
+};
+use ckb_transaction_cobuild_fuzz::{build_tx, input_lock_hashes, lock_hash, FuzzWitness, TxShape};
+use libfuzzer_sys::fuzz_target;
+use molecule::prelude::Entity;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    shape: TxShape,
+    first: Vec<FuzzWitness>,
+    second: Vec<FuzzWitness>,
+}
+
+/// what a digest accepted by a lock group signs besides the fixed inputs
+#[derive(Debug, PartialEq)]
+struct Signed {
+    message: Option<Vec<u8>>,
+    extra_witnesses: Vec<Vec<u8>>,
+}
+
+fn accepted(shape: &TxShape, witnesses: &[FuzzWitness]) -> Vec<([u8; 32], Signed)> {
+    let witnesses: Vec<Vec<u8>> = witnesses.iter().map(FuzzWitness::to_bytes).collect();
+    let (tx, resolved_inputs) = build_tx(shape, &witnesses);
+    let inputs_len = tx.raw().inputs().len();
+    let mut result = Vec::new();
+    for script_hash in input_lock_hashes(shape) {
+        let native =
+            NativeTransaction::new(tx.clone(), resolved_inputs.clone()).lock_group(script_hash);
+        let (digest, seal) = match native.clone().run(parse_message) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        // the seal is the one in the witness of the first input cell of the group
+        let first = (0..inputs_len)
+            .find(|i| {
+                let lock = shape.input_locks.get(*i).copied().unwrap_or(0);
+                lock_hash(lock) == script_hash
+            })
+            .unwrap();
+        let witness = &witnesses[first];
+        let range = classify_witness(witness).seal().unwrap();
+        assert_eq!(seal, witness[range]);
+        // the digest is the same as the off-chain one
+        let message = native.run(fetch_message).unwrap();
+        let expected = signing_message_hash(&message, &tx, &resolved_inputs).unwrap();
+        assert_eq!(digest, expected);
+
+        let signed = Signed {
+            message: message.map(|m| m.as_slice().to_vec()),
+            extra_witnesses: witnesses.iter().skip(inputs_len).cloned().collect(),
+        };
+        result.push((digest, signed));
+    }
+    result
+}
+
+fuzz_target!(|input: Input| {
+    let first = accepted(&input.shape, &input.first);
+    let second = accepted(&input.shape, &input.second);
+    for (digest, signed) in &first {
+        for (other_digest, other_signed) in &second {
+            if digest == other_digest {
+                assert_eq!(signed, other_signed);
+            }
+        }
+    }
+});
@@ -0,0 +1,56 @@
+//! Arbitrary bytes as the raw transaction, with otx batches in witnesses,
+//! through `fetch_otx_batch`, `fetch_non_otx_ranges` and `fetch_otx_messages`:
+//! no panics on malformed offsets, and the batch agrees with `host::otx_batch`
+//! if the raw transaction is well formed.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use ckb_transaction_cobuild::{
+    fetch_non_otx_ranges, fetch_otx_batch, fetch_otx_messages,
+    host::otx_batch,
+    native::NativeTransaction,
+    schemas::{
+        basic::ResolvedInputs,
+        blockchain::{Bytes, BytesVec, RawTransaction, RawTransactionReader, Transaction},
+    },
+};
+use ckb_gen_types::prelude::Pack;
+use ckb_transaction_cobuild_fuzz::FuzzWitness;
+use libfuzzer_sys::fuzz_target;
+use molecule::prelude::{Builder, Entity, Reader};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    raw: Vec<u8>,
+    witnesses: Vec<FuzzWitness>,
+}
+
+fuzz_target!(|input: Input| {
+    let witnesses = input
+        .witnesses
+        .iter()
+        .map(|w| -> Bytes { w.to_bytes().as_slice().pack() });
+    let tx = Transaction::new_builder()
+        .raw(RawTransaction::new_unchecked(input.raw.clone().into()))
+        .witnesses(BytesVec::new_builder().extend(witnesses).build())
+        .build();
+    let native = NativeTransaction::new(tx.clone(), ResolvedInputs::default());
+
+    let fetched = native.clone().run(fetch_otx_batch);
+    let non_otx = native.clone().run(fetch_non_otx_ranges);
+    assert_eq!(fetched.is_ok(), non_otx.is_ok());
+    native.run(|| {
+        if let Ok(messages) = fetch_otx_messages() {
+            messages.for_each(drop);
+        }
+    });
+
+    if RawTransactionReader::verify(&input.raw, false).is_ok() {
+        let expected = otx_batch(&tx);
+        assert_eq!(
+            fetched.map(|batch| batch.otx_count),
+            expected.map(|otxs| otxs.len())
+        );
+    }
+});
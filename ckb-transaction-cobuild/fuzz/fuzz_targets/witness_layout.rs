@@ -0,0 +1,70 @@
+//! Arbitrary witnesses through `classify_witness` and `validate_witnesses`:
+//! no panics, and every range points to a well formed part of the witness.
+
+#![no_main]
+
+use ckb_transaction_cobuild::{
+    classify_witness, classify_witnesses,
+    schemas::{
+        basic::{MessageReader, OtxReader, OtxStartReader, SealPairVecReader},
+        blockchain::BytesReader,
+    },
+    validate_witnesses, WitnessKind,
+};
+use libfuzzer_sys::fuzz_target;
+use molecule::prelude::Reader;
+
+fuzz_target!(|input: (Vec<Vec<u8>>, u8)| {
+    let (witnesses, inputs_len) = input;
+    for witness in &witnesses {
+        match classify_witness(witness) {
+            WitnessKind::SighashAll { seal, message } => {
+                assert!(witness.get(seal).is_some());
+                assert!(MessageReader::verify(&witness[message], false).is_ok());
+            }
+            WitnessKind::SighashAllOnly { seal } => {
+                assert!(witness.get(seal).is_some());
+            }
+            WitnessKind::OtxStart { otx_start } => {
+                assert!(OtxStartReader::verify(&witness[otx_start], false).is_ok());
+            }
+            WitnessKind::Otx {
+                otx,
+                message,
+                seals,
+            } => {
+                assert!(OtxReader::verify(&witness[otx], false).is_ok());
+                assert!(MessageReader::verify(&witness[message], false).is_ok());
+                assert!(SealPairVecReader::verify(&witness[seals], false).is_ok());
+            }
+            WitnessKind::WitnessArgs {
+                lock,
+                input_type,
+                output_type,
+            } => {
+                for field in [lock, input_type, output_type].into_iter().flatten() {
+                    // the raw data follows its 4 bytes length header
+                    let header = field.start.checked_sub(4).unwrap();
+                    assert!(BytesReader::verify(&witness[header..field.end], false).is_ok());
+                }
+            }
+            WitnessKind::Empty => assert!(witness.is_empty()),
+            WitnessKind::Unknown => {}
+        }
+    }
+
+    let report = validate_witnesses(&witnesses, inputs_len as usize);
+    let kinds = classify_witnesses(&witnesses);
+    if let Some(i) = report.sighash_all {
+        assert!(matches!(kinds[i], WitnessKind::SighashAll { .. }));
+    }
+    for i in report.sighash_all_only.iter().copied() {
+        assert!(matches!(kinds[i], WitnessKind::SighashAllOnly { .. }));
+    }
+    if let Some(i) = report.otx_start {
+        assert!(matches!(kinds[i], WitnessKind::OtxStart { .. }));
+    }
+    for i in report.otx.clone() {
+        assert!(matches!(kinds[i], WitnessKind::Otx { .. }));
+    }
+});
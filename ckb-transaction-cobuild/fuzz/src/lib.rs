@@ -0,0 +1,197 @@
+//! Arbitrary transactions for the fuzz targets. Witnesses are either raw
+//! bytes or structured WitnessLayouts, so the fuzzer reaches the parsing of
+//! well formed layouts as well as garbage.
+
+use arbitrary::Arbitrary;
+use ckb_gen_types::prelude::{Builder, Entity, Pack};
+use ckb_transaction_cobuild::{
+    host::ckb_hash,
+    schemas::{
+        basic::{
+            Action, ActionVec, Message, Otx, OtxStart, ResolvedInputs, SealPair, SealPairVec,
+            SighashAll, SighashAllOnly,
+        },
+        blockchain::{
+            Byte32Vec, Bytes, BytesVec, CellDep, CellDepVec, CellInput, CellInputVec, CellOutput,
+            CellOutputVec, OutPoint, RawTransaction, Script, Transaction,
+        },
+        top_level::{WitnessLayout, WitnessLayoutUnion},
+    },
+};
+
+/// number of different lock scripts of input cells
+pub const LOCKS: u8 = 3;
+
+#[derive(Arbitrary, Debug)]
+pub struct TxShape {
+    /// the lock (modulo `LOCKS`) of every input cell
+    pub input_locks: Vec<u8>,
+    pub input_data: Vec<Vec<u8>>,
+    pub outputs: u8,
+    pub cell_deps: u8,
+    pub header_deps: u8,
+}
+
+#[derive(Arbitrary, Debug)]
+pub enum FuzzWitness {
+    Empty,
+    Raw(Vec<u8>),
+    SighashAll {
+        seal: Vec<u8>,
+        actions: Vec<Vec<u8>>,
+    },
+    SighashAllOnly {
+        seal: Vec<u8>,
+    },
+    OtxStart([u8; 4]),
+    Otx {
+        counts: [u8; 4],
+        actions: Vec<Vec<u8>>,
+        /// lock (modulo `LOCKS`) and seal
+        seals: Vec<(u8, Vec<u8>)>,
+    },
+}
+
+pub fn lock_script(lock: u8) -> Script {
+    Script::new_builder()
+        .args([lock % LOCKS].as_slice().pack())
+        .build()
+}
+
+pub fn lock_hash(lock: u8) -> [u8; 32] {
+    ckb_hash(lock_script(lock).as_slice())
+}
+
+fn message(actions: &[Vec<u8>]) -> Message {
+    let actions = actions
+        .iter()
+        .map(|data| Action::new_builder().data(data.as_slice().pack()).build());
+    Message::new_builder()
+        .actions(ActionVec::new_builder().extend(actions).build())
+        .build()
+}
+
+impl FuzzWitness {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let layout = match self {
+            FuzzWitness::Empty => return Vec::new(),
+            FuzzWitness::Raw(data) => return data.clone(),
+            FuzzWitness::SighashAll { seal, actions } => WitnessLayoutUnion::SighashAll(
+                SighashAll::new_builder()
+                    .seal(seal.as_slice().pack())
+                    .message(message(actions))
+                    .build(),
+            ),
+            FuzzWitness::SighashAllOnly { seal } => WitnessLayoutUnion::SighashAllOnly(
+                SighashAllOnly::new_builder()
+                    .seal(seal.as_slice().pack())
+                    .build(),
+            ),
+            FuzzWitness::OtxStart(start) => WitnessLayoutUnion::OtxStart(
+                OtxStart::new_builder()
+                    .start_input_cell((start[0] as u32).pack())
+                    .start_output_cell((start[1] as u32).pack())
+                    .start_cell_deps((start[2] as u32).pack())
+                    .start_header_deps((start[3] as u32).pack())
+                    .build(),
+            ),
+            FuzzWitness::Otx {
+                counts,
+                actions,
+                seals,
+            } => {
+                let seals = seals.iter().map(|(lock, seal)| {
+                    SealPair::new_builder()
+                        .script_hash(lock_hash(*lock).pack())
+                        .seal(seal.as_slice().pack())
+                        .build()
+                });
+                WitnessLayoutUnion::Otx(
+                    Otx::new_builder()
+                        .input_cells((counts[0] as u32).pack())
+                        .output_cells((counts[1] as u32).pack())
+                        .cell_deps((counts[2] as u32).pack())
+                        .header_deps((counts[3] as u32).pack())
+                        .message(message(actions))
+                        .seals(SealPairVec::new_builder().extend(seals).build())
+                        .build(),
+                )
+            }
+        };
+        WitnessLayout::new_builder()
+            .set(layout)
+            .build()
+            .as_slice()
+            .to_vec()
+    }
+}
+
+///
+/// build the transaction and its resolved inputs, at least one input cell
+///
+pub fn build_tx(shape: &TxShape, witnesses: &[Vec<u8>]) -> (Transaction, ResolvedInputs) {
+    let input_locks = if shape.input_locks.is_empty() {
+        &[0u8][..]
+    } else {
+        &shape.input_locks[..]
+    };
+    let inputs = input_locks.iter().enumerate().map(|(i, _)| {
+        CellInput::new_builder()
+            .previous_output(OutPoint::new_builder().index((i as u32).pack()).build())
+            .build()
+    });
+    let cells = input_locks.iter().map(|lock| {
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock_script(*lock))
+            .build()
+    });
+    let cells_data = (0..input_locks.len()).map(|i| {
+        let data = shape.input_data.get(i).cloned().unwrap_or_default();
+        data.as_slice().pack()
+    });
+    let outputs = (0..shape.outputs).map(|_| {
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(lock_script(0))
+            .build()
+    });
+    let outputs_data = (0..shape.outputs).map(|i| [i].as_slice().pack());
+    let cell_deps = (0..shape.cell_deps).map(|i| {
+        CellDep::new_builder()
+            .out_point(OutPoint::new_builder().index((i as u32).pack()).build())
+            .build()
+    });
+    let header_deps = (0..shape.header_deps).map(|i| [i; 32].pack());
+
+    let raw = RawTransaction::new_builder()
+        .inputs(CellInputVec::new_builder().extend(inputs).build())
+        .outputs(CellOutputVec::new_builder().extend(outputs).build())
+        .outputs_data(BytesVec::new_builder().extend(outputs_data).build())
+        .cell_deps(CellDepVec::new_builder().extend(cell_deps).build())
+        .header_deps(Byte32Vec::new_builder().extend(header_deps).build())
+        .build();
+    let witnesses = witnesses.iter().map(|w| -> Bytes { w.as_slice().pack() });
+    let tx = Transaction::new_builder()
+        .raw(raw)
+        .witnesses(BytesVec::new_builder().extend(witnesses).build())
+        .build();
+    let resolved_inputs = ResolvedInputs::new_builder()
+        .outputs(CellOutputVec::new_builder().extend(cells).build())
+        .outputs_data(BytesVec::new_builder().extend(cells_data).build())
+        .build();
+    (tx, resolved_inputs)
+}
+
+///
+/// the distinct lock hashes of the input cells
+///
+pub fn input_lock_hashes(shape: &TxShape) -> Vec<[u8; 32]> {
+    let mut locks: Vec<u8> = shape.input_locks.iter().map(|l| l % LOCKS).collect();
+    if locks.is_empty() {
+        locks.push(0);
+    }
+    locks.sort_unstable();
+    locks.dedup();
+    locks.into_iter().map(lock_hash).collect()
+}
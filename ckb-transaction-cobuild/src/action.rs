@@ -0,0 +1,125 @@
+use crate::{
+    schemas::{
+        basic::{ActionReader, ActionVecReader, MessageReader},
+        spore::SporeActionReader,
+        top_level::{WitnessLayoutReader, WitnessLayoutUnionReader},
+    },
+    syscalls::load_witness,
+    Error,
+};
+use alloc::vec::Vec;
+use ckb_std::{ckb_constants::Source, high_level::QueryIter};
+use molecule::prelude::Reader;
+
+/// MessageWitness is the witness in SighashAll variant. It owns the witness
+/// buffer, the message and its actions are read from the buffer without
+/// converting them to owned entities.
+pub struct MessageWitness(Vec<u8>);
+
+impl MessageWitness {
+    pub fn message(&self) -> MessageReader {
+        match WitnessLayoutReader::new_unchecked(&self.0).to_enum() {
+            WitnessLayoutUnionReader::SighashAll(s) => s.message(),
+            _ => unreachable!(),
+        }
+    }
+
+    ///
+    /// iterate the actions of the message belonging to the script
+    ///
+    pub fn actions(&self, script_hash: [u8; 32]) -> ActionIter {
+        ActionIter::new(self.message(), script_hash)
+    }
+}
+
+///
+/// fetch the witness in SighashAll variant
+/// returns None if there is no SighashAll witness
+/// returns Error::WrongWitnessLayout if there are more than one SighashAll witness
+///
+pub fn fetch_message_witness() -> Result<Option<MessageWitness>, Error> {
+    let mut iter = QueryIter::new(load_witness, Source::Input).filter(|witness| {
+        matches!(
+            WitnessLayoutReader::from_slice(witness).map(|r| r.to_enum()),
+            Ok(WitnessLayoutUnionReader::SighashAll(_))
+        )
+    });
+
+    match (iter.next(), iter.next()) {
+        (Some(witness), None) => Ok(Some(MessageWitness(witness))),
+        (None, None) => Ok(None),
+        _ => Err(Error::WrongWitnessLayout),
+    }
+}
+
+/// ActionIter is an iterator over the actions of a message whose script_hash
+/// is the given one, optionally filtered by script_info_hash too. The
+/// `ActionReader`s are borrowed from the message.
+pub struct ActionIter<'a> {
+    actions: ActionVecReader<'a>,
+    index: usize,
+    script_hash: [u8; 32],
+    script_info_hash: Option<[u8; 32]>,
+}
+
+impl<'a> ActionIter<'a> {
+    pub fn new(message: MessageReader<'a>, script_hash: [u8; 32]) -> Self {
+        Self {
+            actions: message.actions(),
+            index: 0,
+            script_hash,
+            script_info_hash: None,
+        }
+    }
+
+    ///
+    /// only yield the actions with this script_info_hash
+    ///
+    pub fn script_info_hash(mut self, script_info_hash: [u8; 32]) -> Self {
+        self.script_info_hash = Some(script_info_hash);
+        self
+    }
+
+    ///
+    /// returns the only matched action
+    /// returns Error::NoMatchedAction if there is no matched action
+    /// returns Error::MultipleMatchedActions if there are more than one matched action
+    ///
+    pub fn single(mut self) -> Result<ActionReader<'a>, Error> {
+        match (self.next(), self.next()) {
+            (Some(action), None) => Ok(action),
+            (None, None) => Err(Error::NoMatchedAction),
+            _ => Err(Error::MultipleMatchedActions),
+        }
+    }
+
+    fn is_matched(&self, action: &ActionReader) -> bool {
+        action.script_hash().as_slice() == self.script_hash.as_slice()
+            && self.script_info_hash.map_or(true, |script_info_hash| {
+                action.script_info_hash().as_slice() == script_info_hash.as_slice()
+            })
+    }
+}
+
+impl<'a> Iterator for ActionIter<'a> {
+    type Item = ActionReader<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.actions.len() {
+            let action = self.actions.get_unchecked(self.index);
+            self.index += 1;
+            if self.is_matched(&action) {
+                return Some(action);
+            }
+        }
+        None
+    }
+}
+
+///
+/// decode the data of an action as SporeAction
+/// returns Error::MoleculeEncoding if it isn't a valid SporeAction
+///
+pub fn parse_spore_action(action: ActionReader) -> Result<SporeActionReader, Error> {
+    Ok(SporeActionReader::from_slice(action.data().raw_data())?)
+}
@@ -24,3 +24,13 @@ pub fn new_otx_blake2b() -> Blake2b {
         .personal(PERSONALIZATION_OTX)
         .build()
 }
+
+pub const PERSONALIZATION_DEFAULT: &[u8] = b"ckb-default-hash";
+
+/// return a blake2b instance with the default personalization of CKB, used
+/// by the legacy WitnessArgs sighash_all
+pub fn new_default_blake2b() -> Blake2b {
+    Blake2bBuilder::new(32)
+        .personal(PERSONALIZATION_DEFAULT)
+        .build()
+}
@@ -0,0 +1,420 @@
+//! Assemble a `BuildingPacket` off-chain and finalize it into a signed
+//! transaction, see `PacketBuilder` and `finalize`.
+
+use crate::{
+    classify_witness,
+    host::{ckb_hash, signing_message_hash},
+    packet_verifier::{ActionIndex, Finding},
+    schemas::{
+        basic::{
+            Action, ActionReader, ActionVec, BuildingPacket, BuildingPacketUnion, BuildingPacketV1,
+            BuildingPacketV1Reader, Message, ResolvedInputs, ScriptInfo, ScriptInfoVec, SighashAll,
+            SighashAllOnly, Uint32Opt,
+        },
+        blockchain::{Bytes, BytesVec, CellOutput, CellOutputVec, Transaction},
+        top_level::{WitnessLayout, WitnessLayoutUnion},
+    },
+    Error, WitnessKind,
+};
+use ckb_gen_types::prelude::{Pack, Unpack};
+use molecule::prelude::{Builder, Entity, Reader};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildingPacketError {
+    /// the number of resolved inputs doesn't match the inputs of payload
+    ResolvedInputsMismatch { inputs: usize, resolved: usize },
+    /// the outputs and outputs_data of resolved inputs have different lengths
+    ResolvedInputsDataMismatch,
+    /// the change output is beyond the outputs of payload
+    ChangeOutputOutOfBound(u32),
+    /// no ScriptInfo matches the script_info_hash of the action at this index
+    /// (message actions first, then lock actions)
+    MissingScriptInfo(usize),
+    /// the script_hash of the action at this index differs from its ScriptInfo
+    ScriptHashMismatch(usize),
+    /// the action at this index belongs to no script in the transaction, the
+    /// lock actions must belong to lock scripts of input cells
+    UnknownScript(usize),
+    /// the seal is for a witness beyond the input cells
+    SealOutOfBound(usize),
+    /// more than one seal for the witness at this index
+    DuplicateSeal(usize),
+    /// the witness at this index holds something other than SighashAll or
+    /// SighashAllOnly, e.g. OtxStart or Otx of an otx batch
+    WitnessOccupied(usize),
+    /// there are actions in the message but no seal to put them in SighashAll
+    MissingSeal,
+    /// the witness at this index is already SighashAll but has no seal, there
+    /// can be only one SighashAll in a transaction
+    DuplicateSighashAll(usize),
+    /// failed to calculate the signing message hash
+    Digest(Error),
+}
+
+impl From<Error> for BuildingPacketError {
+    fn from(e: Error) -> Self {
+        BuildingPacketError::Digest(e)
+    }
+}
+
+///
+/// the hash of ScriptInfo, used as `script_info_hash` in Action
+///
+pub fn script_info_hash(script_info: &ScriptInfo) -> [u8; 32] {
+    ckb_hash(script_info.as_slice())
+}
+
+/// PacketBuilder assembles a `BuildingPacket` from the payload transaction,
+/// the actions with their ScriptInfo and the resolved inputs.
+pub struct PacketBuilder {
+    payload: Transaction,
+    actions: Vec<Action>,
+    lock_actions: Vec<Action>,
+    script_infos: Vec<ScriptInfo>,
+    resolved_outputs: Vec<CellOutput>,
+    resolved_outputs_data: Vec<Bytes>,
+    change_output: Option<u32>,
+}
+
+impl PacketBuilder {
+    pub fn new(payload: Transaction) -> Self {
+        Self {
+            payload,
+            actions: Vec::new(),
+            lock_actions: Vec::new(),
+            script_infos: Vec::new(),
+            resolved_outputs: Vec::new(),
+            resolved_outputs_data: Vec::new(),
+            change_output: None,
+        }
+    }
+
+    fn new_action(&mut self, script_info: ScriptInfo, data: &[u8]) -> Action {
+        let action = Action::new_builder()
+            .script_info_hash(script_info_hash(&script_info).pack())
+            .script_hash(script_info.script_hash())
+            .data(data.pack())
+            .build();
+        if !self
+            .script_infos
+            .iter()
+            .any(|info| info.as_slice() == script_info.as_slice())
+        {
+            self.script_infos.push(script_info);
+        }
+        action
+    }
+
+    ///
+    /// add an action of the message, for the script described by `script_info`
+    ///
+    pub fn action(mut self, script_info: ScriptInfo, data: &[u8]) -> Self {
+        let action = self.new_action(script_info, data);
+        self.actions.push(action);
+        self
+    }
+
+    ///
+    /// add an action for the lock script described by `script_info`, it's not
+    /// a part of the message
+    ///
+    pub fn lock_action(mut self, script_info: ScriptInfo, data: &[u8]) -> Self {
+        let action = self.new_action(script_info, data);
+        self.lock_actions.push(action);
+        self
+    }
+
+    ///
+    /// add the cell and data of the next input, in the order of inputs in payload
+    ///
+    pub fn resolved_input(mut self, output: CellOutput, data: &[u8]) -> Self {
+        self.resolved_outputs.push(output);
+        self.resolved_outputs_data.push(data.pack());
+        self
+    }
+
+    pub fn change_output(mut self, index: u32) -> Self {
+        self.change_output = Some(index);
+        self
+    }
+
+    ///
+    /// build the BuildingPacket, returns error if it isn't consistent, see `check`
+    ///
+    pub fn build(self) -> Result<BuildingPacket, BuildingPacketError> {
+        let resolved_inputs = ResolvedInputs::new_builder()
+            .outputs(
+                CellOutputVec::new_builder()
+                    .set(self.resolved_outputs)
+                    .build(),
+            )
+            .outputs_data(
+                BytesVec::new_builder()
+                    .set(self.resolved_outputs_data)
+                    .build(),
+            )
+            .build();
+        let packet = BuildingPacketV1::new_builder()
+            .message(
+                Message::new_builder()
+                    .actions(ActionVec::new_builder().set(self.actions).build())
+                    .build(),
+            )
+            .payload(self.payload)
+            .resolved_inputs(resolved_inputs)
+            .change_output(
+                Uint32Opt::new_builder()
+                    .set(self.change_output.map(|index| index.pack()))
+                    .build(),
+            )
+            .script_infos(ScriptInfoVec::new_builder().set(self.script_infos).build())
+            .lock_actions(ActionVec::new_builder().set(self.lock_actions).build())
+            .build();
+        check(&packet)?;
+        Ok(BuildingPacket::new_builder()
+            .set(BuildingPacketUnion::BuildingPacketV1(packet))
+            .build())
+    }
+}
+
+///
+/// check the consistency of a BuildingPacket:
+/// 1. resolved inputs match the inputs of payload
+/// 2. change output is one of the outputs of payload
+/// 3. every action has its ScriptInfo, and belongs to a script in the transaction
+///
+pub fn check(packet: &BuildingPacketV1) -> Result<(), BuildingPacketError> {
+    // actions are indexed with message actions first, then lock actions
+    let message_actions = packet.message().actions().len();
+    let action_index = |index| match index {
+        ActionIndex::Message(i) => i,
+        ActionIndex::Lock(i) => message_actions + i,
+    };
+    // a duplicate ScriptInfo doesn't change what is signed
+    let finding = packet_findings(packet.as_reader())
+        .into_iter()
+        .find(|f| !matches!(f, Finding::DuplicateScriptInfo(_) | Finding::Witness(_)));
+    let err = match finding {
+        None => return Ok(()),
+        Some(Finding::ResolvedInputsMismatch { inputs, resolved }) => {
+            BuildingPacketError::ResolvedInputsMismatch { inputs, resolved }
+        }
+        Some(Finding::ResolvedInputsDataMismatch { .. }) => {
+            BuildingPacketError::ResolvedInputsDataMismatch
+        }
+        Some(Finding::ChangeOutputOutOfBound(index)) => {
+            BuildingPacketError::ChangeOutputOutOfBound(index)
+        }
+        Some(Finding::MissingScriptInfo(index)) => {
+            BuildingPacketError::MissingScriptInfo(action_index(index))
+        }
+        Some(Finding::ScriptHashMismatch { action, .. }) => {
+            BuildingPacketError::ScriptHashMismatch(action_index(action))
+        }
+        Some(Finding::UnknownScript(index)) => {
+            BuildingPacketError::UnknownScript(action_index(index))
+        }
+        Some(Finding::DuplicateScriptInfo(_) | Finding::Witness(_)) => unreachable!(),
+    };
+    Err(err)
+}
+
+///
+/// the problems of a BuildingPacketV1 besides its witnesses, in the order they
+/// are found. `check` rejects the packet with the first one, and
+/// `packet_verifier::verify_packet_v1` reports them all.
+///
+pub(crate) fn packet_findings(packet: BuildingPacketV1Reader) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let raw = packet.payload().raw();
+    let resolved_inputs = packet.resolved_inputs();
+
+    // resolved inputs
+    let inputs = raw.inputs().len();
+    let resolved = resolved_inputs.outputs().len();
+    if inputs != resolved {
+        findings.push(Finding::ResolvedInputsMismatch { inputs, resolved });
+    }
+    let outputs_data = resolved_inputs.outputs_data().len();
+    if outputs_data != resolved {
+        findings.push(Finding::ResolvedInputsDataMismatch {
+            outputs: resolved,
+            outputs_data,
+        });
+    }
+
+    // change output
+    if let Some(index) = packet.change_output().to_opt() {
+        let index: u32 = index.unpack();
+        if index as usize >= raw.outputs().len() {
+            findings.push(Finding::ChangeOutputOutOfBound(index));
+        }
+    }
+
+    // script infos
+    let script_info_hashes: Vec<[u8; 32]> = packet
+        .script_infos()
+        .iter()
+        .map(|info| script_info_hash(&info.to_entity()))
+        .collect();
+    for (index, hash) in script_info_hashes.iter().enumerate() {
+        if script_info_hashes[..index].contains(hash) {
+            findings.push(Finding::DuplicateScriptInfo(index));
+        }
+    }
+
+    // actions
+    let lock_hashes: Vec<[u8; 32]> = resolved_inputs
+        .outputs()
+        .iter()
+        .map(|cell| ckb_hash(cell.lock().as_slice()))
+        .collect();
+    let type_hashes: Vec<[u8; 32]> = resolved_inputs
+        .outputs()
+        .iter()
+        .chain(raw.outputs().iter())
+        .filter_map(|cell| cell.type_().to_opt())
+        .map(|script| ckb_hash(script.as_slice()))
+        .collect();
+    let verify_action = |findings: &mut Vec<Finding>, index: ActionIndex, action: ActionReader| {
+        match script_info_hashes
+            .iter()
+            .position(|hash| hash == action.script_info_hash().as_slice())
+        {
+            Some(script_info) => {
+                let info = packet.script_infos().get_unchecked(script_info);
+                if info.script_hash().as_slice() != action.script_hash().as_slice() {
+                    findings.push(Finding::ScriptHashMismatch {
+                        action: index,
+                        script_info,
+                    });
+                }
+            }
+            None => findings.push(Finding::MissingScriptInfo(index)),
+        }
+        let script_hash = action.script_hash().as_slice();
+        let is_lock_action = matches!(index, ActionIndex::Lock(_));
+        let known = lock_hashes.iter().any(|hash| hash == script_hash)
+            || (!is_lock_action && type_hashes.iter().any(|hash| hash == script_hash));
+        if !known {
+            findings.push(Finding::UnknownScript(index));
+        }
+    };
+    for (index, action) in packet.message().actions().iter().enumerate() {
+        verify_action(&mut findings, ActionIndex::Message(index), action);
+    }
+    for (index, action) in packet.lock_actions().iter().enumerate() {
+        verify_action(&mut findings, ActionIndex::Lock(index), action);
+    }
+
+    findings
+}
+
+///
+/// the message put into SighashAll, None if there is no action, then only
+/// SighashAllOnly is used
+///
+fn packet_message(packet: &BuildingPacketV1) -> Option<Message> {
+    let message = packet.message();
+    if message.actions().is_empty() {
+        None
+    } else {
+        Some(message)
+    }
+}
+
+///
+/// the signing message hash every lock script in the transaction verifies,
+/// it's what the wallet signs
+///
+pub fn packet_signing_message_hash(packet: &BuildingPacketV1) -> Result<[u8; 32], Error> {
+    signing_message_hash(
+        &packet_message(packet),
+        &packet.payload(),
+        &packet.resolved_inputs(),
+    )
+}
+
+///
+/// finalize the BuildingPacket into a signed transaction. `seals` are pairs of
+/// witness index (the first input cell of a script group) and seal. The seal
+/// with the lowest index is put into SighashAll together with the message,
+/// the others into SighashAllOnly. If there is no action in the message, all
+/// seals are put into SighashAllOnly. The witnesses of seals must be empty or
+/// SighashAll/SighashAllOnly, other layouts such as an otx batch are kept.
+/// If there are actions, at least one seal is required and no other witness
+/// can be SighashAll.
+///
+pub fn finalize(
+    packet: &BuildingPacketV1,
+    seals: &[(usize, Vec<u8>)],
+) -> Result<Transaction, BuildingPacketError> {
+    check(packet)?;
+    let payload = packet.payload();
+    let inputs_len = payload.raw().inputs().len();
+    let mut sorted = BTreeMap::new();
+    for (index, seal) in seals {
+        if *index >= inputs_len {
+            return Err(BuildingPacketError::SealOutOfBound(*index));
+        }
+        if sorted.insert(*index, seal).is_some() {
+            return Err(BuildingPacketError::DuplicateSeal(*index));
+        }
+    }
+
+    let mut witnesses: Vec<Bytes> = payload.witnesses().into_iter().collect();
+    // only empty witnesses and the seals signed before are replaced
+    for index in sorted.keys() {
+        if let Some(witness) = witnesses.get(*index) {
+            let kind = classify_witness(&witness.raw_data());
+            if kind != WitnessKind::Empty && !kind.is_sighash() {
+                return Err(BuildingPacketError::WitnessOccupied(*index));
+            }
+        }
+    }
+    let mut message = packet_message(packet);
+    if message.is_some() {
+        if sorted.is_empty() {
+            return Err(BuildingPacketError::MissingSeal);
+        }
+        // the seals signed before are replaced, any other SighashAll would be
+        // a second one
+        if let Some(index) = (0..witnesses.len()).find(|i| {
+            !sorted.contains_key(i)
+                && matches!(
+                    classify_witness(&witnesses[*i].raw_data()),
+                    WitnessKind::SighashAll { .. }
+                )
+        }) {
+            return Err(BuildingPacketError::DuplicateSighashAll(index));
+        }
+    }
+    for (index, seal) in sorted {
+        let layout = match message.take() {
+            Some(message) => WitnessLayoutUnion::SighashAll(
+                SighashAll::new_builder()
+                    .message(message)
+                    .seal(seal.as_slice().pack())
+                    .build(),
+            ),
+            None => WitnessLayoutUnion::SighashAllOnly(
+                SighashAllOnly::new_builder()
+                    .seal(seal.as_slice().pack())
+                    .build(),
+            ),
+        };
+        if witnesses.len() <= index {
+            witnesses.resize(index + 1, Default::default());
+        }
+        witnesses[index] = WitnessLayout::new_builder()
+            .set(layout)
+            .build()
+            .as_slice()
+            .pack();
+    }
+    Ok(payload
+        .as_builder()
+        .witnesses(BytesVec::new_builder().set(witnesses).build())
+        .build())
+}
@@ -0,0 +1,132 @@
+use crate::{
+    schemas::{
+        blockchain::{BytesOptReader, WitnessArgsReader},
+        top_level::{WitnessLayoutReader, WitnessLayoutUnionReader},
+    },
+    syscalls::load_witness,
+};
+use alloc::vec::Vec;
+use ckb_std::{ckb_constants::Source, high_level::QueryIter};
+use core::ops::Range;
+use molecule::prelude::Reader;
+
+/// The layout of a witness. The ranges are offsets into the raw witness bytes:
+/// `seal` and the fields of WitnessArgs are the raw data without the length
+/// header, the others are the molecule serialized structures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessKind {
+    SighashAll {
+        seal: Range<usize>,
+        message: Range<usize>,
+    },
+    SighashAllOnly {
+        seal: Range<usize>,
+    },
+    OtxStart {
+        otx_start: Range<usize>,
+    },
+    Otx {
+        otx: Range<usize>,
+        message: Range<usize>,
+        seals: Range<usize>,
+    },
+    /// the legacy WitnessArgs, None for absent fields
+    WitnessArgs {
+        lock: Option<Range<usize>>,
+        input_type: Option<Range<usize>>,
+        output_type: Option<Range<usize>>,
+    },
+    Empty,
+    Unknown,
+}
+
+impl WitnessKind {
+    pub fn is_sighash(&self) -> bool {
+        matches!(
+            self,
+            WitnessKind::SighashAll { .. } | WitnessKind::SighashAllOnly { .. }
+        )
+    }
+
+    ///
+    /// the seal of SighashAll or SighashAllOnly
+    ///
+    pub fn seal(&self) -> Option<Range<usize>> {
+        match self {
+            WitnessKind::SighashAll { seal, .. } | WitnessKind::SighashAllOnly { seal } => {
+                Some(seal.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+///
+/// the range of `part` in `witness`, `part` must be a sub slice of `witness`
+///
+fn range_of(witness: &[u8], part: &[u8]) -> Range<usize> {
+    let start = part.as_ptr() as usize - witness.as_ptr() as usize;
+    start..start + part.len()
+}
+
+fn opt_range_of(witness: &[u8], field: BytesOptReader) -> Option<Range<usize>> {
+    field
+        .to_opt()
+        .map(|bytes| range_of(witness, bytes.raw_data()))
+}
+
+///
+/// classify a witness. This function doesn't depend on syscalls, it can be
+/// used by off-chain tools.
+///
+pub fn classify_witness(witness: &[u8]) -> WitnessKind {
+    if witness.is_empty() {
+        return WitnessKind::Empty;
+    }
+    if let Ok(r) = WitnessLayoutReader::from_slice(witness) {
+        return match r.to_enum() {
+            WitnessLayoutUnionReader::SighashAll(s) => WitnessKind::SighashAll {
+                seal: range_of(witness, s.seal().raw_data()),
+                message: range_of(witness, s.message().as_slice()),
+            },
+            WitnessLayoutUnionReader::SighashAllOnly(s) => WitnessKind::SighashAllOnly {
+                seal: range_of(witness, s.seal().raw_data()),
+            },
+            WitnessLayoutUnionReader::OtxStart(o) => WitnessKind::OtxStart {
+                otx_start: range_of(witness, o.as_slice()),
+            },
+            WitnessLayoutUnionReader::Otx(o) => WitnessKind::Otx {
+                otx: range_of(witness, o.as_slice()),
+                message: range_of(witness, o.message().as_slice()),
+                seals: range_of(witness, o.seals().as_slice()),
+            },
+        };
+    }
+    if let Ok(r) = WitnessArgsReader::from_slice(witness) {
+        return WitnessKind::WitnessArgs {
+            lock: opt_range_of(witness, r.lock()),
+            input_type: opt_range_of(witness, r.input_type()),
+            output_type: opt_range_of(witness, r.output_type()),
+        };
+    }
+    WitnessKind::Unknown
+}
+
+///
+/// classify the witnesses, the result is indexed by witness index
+///
+pub fn classify_witnesses<W: AsRef<[u8]>>(witnesses: &[W]) -> Vec<WitnessKind> {
+    witnesses
+        .iter()
+        .map(|witness| classify_witness(witness.as_ref()))
+        .collect()
+}
+
+///
+/// classify all witnesses in current transaction, the result is indexed by witness index
+///
+pub fn classify_current_transaction() -> Vec<WitnessKind> {
+    QueryIter::new(load_witness, Source::Input)
+        .map(|witness| classify_witness(&witness))
+        .collect()
+}
@@ -0,0 +1,194 @@
+//! `DigestHasher` feeds the signing message hashers of SighashAll,
+//! SighashAllOnly and OTX. When tracing is enabled it also splits the hashed
+//! data into named components, so a seal rejected on-chain can be compared
+//! part by part with the digest calculated off-chain.
+//!
+//! Scripts built with the `debug` feature log the components with `debug!`,
+//! `host::explain_signing_message_hash` and `host::explain_otx_signing_message_hash`
+//! return the same components. Both are printed line by line in the same
+//! format, see `DigestTrace`.
+
+use crate::blake2b::{
+    new_default_blake2b, new_otx_blake2b, new_sighash_all_blake2b, new_sighash_all_only_blake2b,
+    Blake2b, PERSONALIZATION_OTX, PERSONALIZATION_SIGHASH_ALL, PERSONALIZATION_SIGHASH_ALL_ONLY,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// A part of the data fed into a signing message hasher
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DigestComponent {
+    pub name: String,
+    /// offset in the whole hashed data
+    pub offset: usize,
+    pub len: usize,
+    /// blake2b with the default personalization of CKB over the component
+    pub hash: [u8; 32],
+}
+
+/// The signing message hash with every component fed into the hasher
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DigestTrace {
+    pub personalization: &'static [u8],
+    /// empty if tracing is disabled
+    pub components: Vec<DigestComponent>,
+    pub digest: [u8; 32],
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+    write!(f, "0x")?;
+    for b in data {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for DigestComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} offset {} len {} hash ",
+            self.name, self.offset, self.len
+        )?;
+        write_hex(f, &self.hash)
+    }
+}
+
+impl fmt::Display for DigestTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "personalization {}",
+            core::str::from_utf8(self.personalization).unwrap_or("?")
+        )?;
+        for component in &self.components {
+            writeln!(f, "{}", component)?;
+        }
+        write!(f, "digest ")?;
+        write_hex(f, &self.digest)
+    }
+}
+
+impl DigestTrace {
+    ///
+    /// log every line of the trace with `debug!`
+    ///
+    #[cfg(feature = "debug")]
+    pub fn log(&self) {
+        for line in alloc::string::ToString::to_string(self).lines() {
+            ckb_std::debug!("{}", line);
+        }
+    }
+}
+
+struct Tracer {
+    offset: usize,
+    current: Option<(String, usize, Blake2b)>,
+    components: Vec<DigestComponent>,
+}
+
+impl Tracer {
+    fn end(&mut self) {
+        if let Some((name, offset, hasher)) = self.current.take() {
+            let mut hash = [0u8; 32];
+            hasher.finalize(&mut hash);
+            self.components.push(DigestComponent {
+                name,
+                offset,
+                len: self.offset - offset,
+                hash,
+            });
+        }
+    }
+}
+
+/// DigestHasher is a signing message hasher, optionally tracing the
+/// components fed into it
+pub struct DigestHasher {
+    hasher: Blake2b,
+    personalization: &'static [u8],
+    tracer: Option<Tracer>,
+}
+
+impl DigestHasher {
+    fn new(hasher: Blake2b, personalization: &'static [u8], trace: bool) -> Self {
+        Self {
+            hasher,
+            personalization,
+            tracer: if trace {
+                Some(Tracer {
+                    offset: 0,
+                    current: None,
+                    components: Vec::new(),
+                })
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn sighash_all(trace: bool) -> Self {
+        Self::new(
+            new_sighash_all_blake2b(),
+            PERSONALIZATION_SIGHASH_ALL,
+            trace,
+        )
+    }
+
+    pub fn sighash_all_only(trace: bool) -> Self {
+        Self::new(
+            new_sighash_all_only_blake2b(),
+            PERSONALIZATION_SIGHASH_ALL_ONLY,
+            trace,
+        )
+    }
+
+    pub fn otx(trace: bool) -> Self {
+        Self::new(new_otx_blake2b(), PERSONALIZATION_OTX, trace)
+    }
+
+    ///
+    /// start a new component, the name is only built if tracing is enabled
+    ///
+    pub fn begin<F: FnOnce() -> String>(&mut self, name: F) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.end();
+            tracer.current = Some((name(), tracer.offset, new_default_blake2b()));
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.offset += data.len();
+            if let Some((_, _, hasher)) = tracer.current.as_mut() {
+                hasher.update(data);
+            }
+        }
+    }
+
+    ///
+    /// `begin` a component and `update` it with `data`
+    ///
+    pub fn component<F: FnOnce() -> String>(&mut self, name: F, data: &[u8]) {
+        self.begin(name);
+        self.update(data);
+    }
+
+    pub fn finalize(self) -> DigestTrace {
+        let mut digest = [0u8; 32];
+        self.hasher.finalize(&mut digest);
+        let components = match self.tracer {
+            Some(mut tracer) => {
+                tracer.end();
+                tracer.components
+            }
+            None => Vec::new(),
+        };
+        DigestTrace {
+            personalization: self.personalization,
+            components,
+            digest,
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! The off-chain twin of the signing message hash calculation in scripts.
+//! Wallets and backends can use these functions to get the exact digests
+//! scripts verify, from a packed `Transaction` and its `ResolvedInputs`.
+//!
+//! An item missing in the transaction or in `ResolvedInputs` is reported as
+//! `Error::Sys(SysError::IndexOutOfBound)`, the same as loading it by syscall.
+
+use crate::{
+    blake2b::new_default_blake2b,
+    digest::{DigestHasher, DigestTrace},
+    otx::advance,
+    schemas::{
+        basic::{Message, ResolvedInputs},
+        blockchain::Transaction,
+        top_level::{WitnessLayoutReader, WitnessLayoutUnionReader},
+    },
+    Error, OtxRanges,
+};
+use ckb_gen_types::prelude::Unpack;
+use ckb_std::error::SysError;
+use molecule::prelude::{Entity, Reader};
+
+///
+/// blake2b with the default personalization of CKB, the same as script hash,
+/// transaction hash etc.
+///
+pub fn ckb_hash(data: &[u8]) -> [u8; 32] {
+    let mut hasher = new_default_blake2b();
+    hasher.update(data);
+    let mut result = [0u8; 32];
+    hasher.finalize(&mut result);
+    result
+}
+
+///
+/// the transaction hash, blake2b of the molecule serialized raw transaction
+///
+pub fn tx_hash(tx: &Transaction) -> [u8; 32] {
+    ckb_hash(tx.raw().as_slice())
+}
+
+///
+/// hash the input cell and its data (length first, 4 bytes little endian)
+///
+fn hash_input_cell(
+    hasher: &mut DigestHasher,
+    resolved_inputs: &ResolvedInputs,
+    index: usize,
+) -> Result<(), Error> {
+    let input_cell = resolved_inputs
+        .outputs()
+        .get(index)
+        .ok_or(SysError::IndexOutOfBound)?;
+    let input_cell_data = resolved_inputs
+        .outputs_data()
+        .get(index)
+        .ok_or(SysError::IndexOutOfBound)?;
+    hasher.component(|| format!("input cell {}", index), input_cell.as_slice());
+    hasher.component(
+        || format!("input cell data length {}", index),
+        &(input_cell_data.len() as u32).to_le_bytes(),
+    );
+    hasher.component(
+        || format!("input cell data {}", index),
+        &input_cell_data.raw_data(),
+    );
+    Ok(())
+}
+
+///
+/// the signing message hash of SighashAll if `message` is given, otherwise
+/// of SighashAllOnly. It's the same as what `parse_message` returns in scripts.
+///
+pub fn signing_message_hash(
+    message: &Option<Message>,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+) -> Result<[u8; 32], Error> {
+    Ok(trace_signing_message_hash(message, tx, resolved_inputs, false)?.digest)
+}
+
+///
+/// `signing_message_hash` with every hashed component, the same as scripts
+/// built with the `debug` feature log
+///
+pub fn explain_signing_message_hash(
+    message: &Option<Message>,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+) -> Result<DigestTrace, Error> {
+    trace_signing_message_hash(message, tx, resolved_inputs, true)
+}
+
+fn trace_signing_message_hash(
+    message: &Option<Message>,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+    trace: bool,
+) -> Result<DigestTrace, Error> {
+    // message
+    let mut hasher = match message {
+        Some(m) => {
+            let mut hasher = DigestHasher::sighash_all(trace);
+            hasher.component(|| "message".into(), m.as_slice());
+            hasher
+        }
+        None => DigestHasher::sighash_all_only(trace),
+    };
+    // tx hash
+    hasher.component(|| "tx hash".into(), &tx_hash(tx));
+    // inputs cell and data
+    let inputs_len = tx.raw().inputs().len();
+    for i in 0..inputs_len {
+        hash_input_cell(&mut hasher, resolved_inputs, i)?;
+    }
+    // extra witnesses
+    for (i, witness) in tx.witnesses().into_iter().enumerate().skip(inputs_len) {
+        hasher.component(
+            || format!("witness length {}", i),
+            &(witness.len() as u32).to_le_bytes(),
+        );
+        hasher.component(|| format!("witness {}", i), &witness.raw_data());
+    }
+
+    Ok(hasher.finalize())
+}
+
+///
+/// the signing message hash of SighashAll with `message`
+///
+pub fn sighash_all_message_hash(
+    message: &Message,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+) -> Result<[u8; 32], Error> {
+    signing_message_hash(&Some(message.clone()), tx, resolved_inputs)
+}
+
+///
+/// the signing message hash of SighashAllOnly
+///
+pub fn sighash_all_only_message_hash(
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+) -> Result<[u8; 32], Error> {
+    signing_message_hash(&None, tx, resolved_inputs)
+}
+
+///
+/// the signing message hash of an otx with `message`, covering the input
+/// cells, output cells, cell deps and header deps in `ranges`. It's the same
+/// as what `OtxMessageIter` yields in scripts.
+///
+pub fn otx_signing_message_hash(
+    message: &Message,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+    ranges: &OtxRanges,
+) -> Result<[u8; 32], Error> {
+    Ok(trace_otx_signing_message_hash(message, tx, resolved_inputs, ranges, false)?.digest)
+}
+
+///
+/// `otx_signing_message_hash` with every hashed component, the same as
+/// scripts built with the `debug` feature log
+///
+pub fn explain_otx_signing_message_hash(
+    message: &Message,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+    ranges: &OtxRanges,
+) -> Result<DigestTrace, Error> {
+    trace_otx_signing_message_hash(message, tx, resolved_inputs, ranges, true)
+}
+
+fn trace_otx_signing_message_hash(
+    message: &Message,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+    ranges: &OtxRanges,
+    trace: bool,
+) -> Result<DigestTrace, Error> {
+    let raw = tx.raw();
+    let mut hasher = DigestHasher::otx(trace);
+    // message
+    hasher.component(|| "message".into(), message.as_slice());
+
+    // otx inputs
+    hasher.component(
+        || "input cells count".into(),
+        &(ranges.input_cells.len() as u32).to_le_bytes(),
+    );
+    for i in ranges.input_cells.clone() {
+        let input = raw.inputs().get(i).ok_or(SysError::IndexOutOfBound)?;
+        hasher.component(|| format!("input {}", i), input.as_slice());
+        hash_input_cell(&mut hasher, resolved_inputs, i)?;
+    }
+
+    // otx outputs
+    hasher.component(
+        || "output cells count".into(),
+        &(ranges.output_cells.len() as u32).to_le_bytes(),
+    );
+    for i in ranges.output_cells.clone() {
+        let output = raw.outputs().get(i).ok_or(SysError::IndexOutOfBound)?;
+        let output_data = raw.outputs_data().get(i).ok_or(SysError::IndexOutOfBound)?;
+        hasher.component(|| format!("output cell {}", i), output.as_slice());
+        // the molecule serialized outputs_data item: length and then the data
+        hasher.component(
+            || format!("output cell data length {}", i),
+            &(output_data.len() as u32).to_le_bytes(),
+        );
+        hasher.component(
+            || format!("output cell data {}", i),
+            &output_data.raw_data(),
+        );
+    }
+
+    // otx cell deps
+    hasher.component(
+        || "cell deps count".into(),
+        &(ranges.cell_deps.len() as u32).to_le_bytes(),
+    );
+    for i in ranges.cell_deps.clone() {
+        let cell_dep = raw.cell_deps().get(i).ok_or(SysError::IndexOutOfBound)?;
+        hasher.component(|| format!("cell dep {}", i), cell_dep.as_slice());
+    }
+
+    // otx header deps
+    hasher.component(
+        || "header deps count".into(),
+        &(ranges.header_deps.len() as u32).to_le_bytes(),
+    );
+    for i in ranges.header_deps.clone() {
+        let header_dep = raw.header_deps().get(i).ok_or(SysError::IndexOutOfBound)?;
+        hasher.component(|| format!("header dep {}", i), header_dep.as_slice());
+    }
+
+    Ok(hasher.finalize())
+}
+
+///
+/// the otx batch of the transaction: the index of every Otx witness and the
+/// items it covers. The errors are the same as `fetch_otx_batch` in scripts,
+/// Error::WrongOtxStart if there is no otx batch.
+///
+pub fn otx_batch(tx: &Transaction) -> Result<Vec<(usize, OtxRanges)>, Error> {
+    // locate OtxStart and the Otx witnesses following it, the layout is
+    // checked over all witnesses before any range, like `fetch_otx_start`
+    let witnesses: Vec<_> = tx.witnesses().into_iter().map(|w| w.raw_data()).collect();
+    let mut start = None;
+    let mut otxs = Vec::new();
+    let mut batch_end = 0;
+    for (i, witness) in witnesses.iter().enumerate() {
+        let layout = match WitnessLayoutReader::from_slice(witness) {
+            Ok(layout) => layout,
+            Err(_) => continue,
+        };
+        match layout.to_enum() {
+            WitnessLayoutUnionReader::OtxStart(otx_start) => {
+                if start.is_some() {
+                    return Err(Error::WrongWitnessLayout);
+                }
+                let values: [u32; 4] = [
+                    otx_start.start_input_cell().unpack(),
+                    otx_start.start_output_cell().unpack(),
+                    otx_start.start_cell_deps().unpack(),
+                    otx_start.start_header_deps().unpack(),
+                ];
+                start = Some(values);
+                batch_end = i;
+            }
+            WitnessLayoutUnionReader::Otx(otx) => {
+                if start.is_none() || batch_end + 1 != i {
+                    return Err(Error::WrongWitnessLayout);
+                }
+                let counts: [u32; 4] = [
+                    otx.input_cells().unpack(),
+                    otx.output_cells().unpack(),
+                    otx.cell_deps().unpack(),
+                    otx.header_deps().unpack(),
+                ];
+                otxs.push((i, counts));
+                batch_end = i;
+            }
+            _ => {}
+        }
+    }
+    let start = match start {
+        Some(start) if !otxs.is_empty() => start,
+        _ => return Err(Error::WrongOtxStart),
+    };
+
+    let raw = tx.raw();
+    let lens = [
+        raw.inputs().len(),
+        raw.outputs().len(),
+        raw.cell_deps().len(),
+        raw.header_deps().len(),
+    ];
+    if start.iter().zip(lens).any(|(s, len)| *s as usize > len) {
+        return Err(Error::OtxStartOutOfBound);
+    }
+    let mut covered = start.map(|s| s as usize..s as usize);
+    let mut batch = Vec::with_capacity(otxs.len());
+    for (otx_index, (i, counts)) in otxs.into_iter().enumerate() {
+        let out_of_bound = Error::OtxOutOfBound(otx_index);
+        let mut next = |k: usize| advance(&mut covered[k], counts[k], lens[k]).ok_or(out_of_bound);
+        let ranges = OtxRanges {
+            input_cells: next(0)?,
+            output_cells: next(1)?,
+            cell_deps: next(2)?,
+            header_deps: next(3)?,
+        };
+        batch.push((i, ranges));
+    }
+    Ok(batch)
+}
@@ -0,0 +1,444 @@
+//! The JSON representation of cobuild types, for exchanging them with JS
+//! tooling. The conversions between these types and the molecule types are
+//! lossless.
+//!
+//! The conventions follow ckb-jsonrpc-types: bytes and hashes are hex strings
+//! with 0x prefix, numbers are hex strings too, fields are in snake case.
+//! Unions are objects with `type` (the item name) and `value`. The strings of
+//! ScriptInfo are bytes, since molecule doesn't require them to be UTF-8.
+
+use crate::schemas::{basic, blockchain, top_level};
+use ckb_jsonrpc_types::{CellOutput, JsonBytes, Transaction, Uint32};
+use ckb_types::{
+    prelude::{Pack, Unpack},
+    H256,
+};
+use molecule::prelude::{Builder, Entity};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Action {
+    pub script_info_hash: H256,
+    pub script_hash: H256,
+    pub data: JsonBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Message {
+    pub actions: Vec<Action>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptInfo {
+    pub name: JsonBytes,
+    pub url: JsonBytes,
+    pub script_hash: H256,
+    pub schema: JsonBytes,
+    pub message_type: JsonBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResolvedInputs {
+    pub outputs: Vec<CellOutput>,
+    pub outputs_data: Vec<JsonBytes>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildingPacketV1 {
+    pub message: Message,
+    pub payload: Transaction,
+    pub resolved_inputs: ResolvedInputs,
+    pub change_output: Option<Uint32>,
+    pub script_infos: Vec<ScriptInfo>,
+    pub lock_actions: Vec<Action>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum BuildingPacket {
+    BuildingPacketV1(BuildingPacketV1),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SighashAll {
+    pub message: Message,
+    pub seal: JsonBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SighashAllOnly {
+    pub seal: JsonBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealPair {
+    pub script_hash: H256,
+    pub seal: JsonBytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtxStart {
+    pub start_input_cell: Uint32,
+    pub start_output_cell: Uint32,
+    pub start_cell_deps: Uint32,
+    pub start_header_deps: Uint32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Otx {
+    pub input_cells: Uint32,
+    pub output_cells: Uint32,
+    pub cell_deps: Uint32,
+    pub header_deps: Uint32,
+    pub message: Message,
+    pub seals: Vec<SealPair>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum WitnessLayout {
+    SighashAll(SighashAll),
+    SighashAllOnly(SighashAllOnly),
+    Otx(Otx),
+    OtxStart(OtxStart),
+}
+
+fn json_string(s: basic::String) -> JsonBytes {
+    JsonBytes::from_vec(s.raw_data().to_vec())
+}
+
+///
+/// String is a byte vector, the same as Bytes in molecule
+///
+fn mol_string(bytes: JsonBytes) -> basic::String {
+    basic::String::new_unchecked(bytes.as_bytes().pack().as_bytes())
+}
+
+fn mol_uint32(n: Uint32) -> blockchain::Uint32 {
+    n.value().pack()
+}
+
+impl From<basic::Action> for Action {
+    fn from(action: basic::Action) -> Self {
+        Self {
+            script_info_hash: action.script_info_hash().unpack(),
+            script_hash: action.script_hash().unpack(),
+            data: action.data().into(),
+        }
+    }
+}
+
+impl From<Action> for basic::Action {
+    fn from(action: Action) -> Self {
+        basic::Action::new_builder()
+            .script_info_hash(action.script_info_hash.pack())
+            .script_hash(action.script_hash.pack())
+            .data(action.data.into())
+            .build()
+    }
+}
+
+impl From<basic::Message> for Message {
+    fn from(message: basic::Message) -> Self {
+        Self {
+            actions: message.actions().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Message> for basic::Message {
+    fn from(message: Message) -> Self {
+        basic::Message::new_builder()
+            .actions(
+                basic::ActionVec::new_builder()
+                    .set(message.actions.into_iter().map(Into::into).collect())
+                    .build(),
+            )
+            .build()
+    }
+}
+
+impl From<basic::ScriptInfo> for ScriptInfo {
+    fn from(script_info: basic::ScriptInfo) -> Self {
+        Self {
+            name: json_string(script_info.name()),
+            url: json_string(script_info.url()),
+            script_hash: script_info.script_hash().unpack(),
+            schema: json_string(script_info.schema()),
+            message_type: json_string(script_info.message_type()),
+        }
+    }
+}
+
+impl From<ScriptInfo> for basic::ScriptInfo {
+    fn from(script_info: ScriptInfo) -> Self {
+        basic::ScriptInfo::new_builder()
+            .name(mol_string(script_info.name))
+            .url(mol_string(script_info.url))
+            .script_hash(script_info.script_hash.pack())
+            .schema(mol_string(script_info.schema))
+            .message_type(mol_string(script_info.message_type))
+            .build()
+    }
+}
+
+impl From<basic::ResolvedInputs> for ResolvedInputs {
+    fn from(resolved_inputs: basic::ResolvedInputs) -> Self {
+        Self {
+            outputs: resolved_inputs
+                .outputs()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            outputs_data: resolved_inputs
+                .outputs_data()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<ResolvedInputs> for basic::ResolvedInputs {
+    fn from(resolved_inputs: ResolvedInputs) -> Self {
+        basic::ResolvedInputs::new_builder()
+            .outputs(
+                blockchain::CellOutputVec::new_builder()
+                    .set(
+                        resolved_inputs
+                            .outputs
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
+                    )
+                    .build(),
+            )
+            .outputs_data(
+                blockchain::BytesVec::new_builder()
+                    .set(
+                        resolved_inputs
+                            .outputs_data
+                            .into_iter()
+                            .map(Into::into)
+                            .collect(),
+                    )
+                    .build(),
+            )
+            .build()
+    }
+}
+
+impl From<basic::BuildingPacketV1> for BuildingPacketV1 {
+    fn from(packet: basic::BuildingPacketV1) -> Self {
+        Self {
+            message: packet.message().into(),
+            payload: packet.payload().into(),
+            resolved_inputs: packet.resolved_inputs().into(),
+            change_output: packet.change_output().to_opt().map(|index| {
+                let index: u32 = index.unpack();
+                index.into()
+            }),
+            script_infos: packet.script_infos().into_iter().map(Into::into).collect(),
+            lock_actions: packet.lock_actions().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<BuildingPacketV1> for basic::BuildingPacketV1 {
+    fn from(packet: BuildingPacketV1) -> Self {
+        basic::BuildingPacketV1::new_builder()
+            .message(packet.message.into())
+            .payload(packet.payload.into())
+            .resolved_inputs(packet.resolved_inputs.into())
+            .change_output(
+                basic::Uint32Opt::new_builder()
+                    .set(packet.change_output.map(mol_uint32))
+                    .build(),
+            )
+            .script_infos(
+                basic::ScriptInfoVec::new_builder()
+                    .set(packet.script_infos.into_iter().map(Into::into).collect())
+                    .build(),
+            )
+            .lock_actions(
+                basic::ActionVec::new_builder()
+                    .set(packet.lock_actions.into_iter().map(Into::into).collect())
+                    .build(),
+            )
+            .build()
+    }
+}
+
+impl From<basic::BuildingPacket> for BuildingPacket {
+    fn from(packet: basic::BuildingPacket) -> Self {
+        match packet.to_enum() {
+            basic::BuildingPacketUnion::BuildingPacketV1(packet) => {
+                BuildingPacket::BuildingPacketV1(packet.into())
+            }
+        }
+    }
+}
+
+impl From<BuildingPacket> for basic::BuildingPacket {
+    fn from(packet: BuildingPacket) -> Self {
+        let packet = match packet {
+            BuildingPacket::BuildingPacketV1(packet) => {
+                basic::BuildingPacketUnion::BuildingPacketV1(packet.into())
+            }
+        };
+        basic::BuildingPacket::new_builder().set(packet).build()
+    }
+}
+
+impl From<basic::SighashAll> for SighashAll {
+    fn from(sighash_all: basic::SighashAll) -> Self {
+        Self {
+            message: sighash_all.message().into(),
+            seal: sighash_all.seal().into(),
+        }
+    }
+}
+
+impl From<SighashAll> for basic::SighashAll {
+    fn from(sighash_all: SighashAll) -> Self {
+        basic::SighashAll::new_builder()
+            .message(sighash_all.message.into())
+            .seal(sighash_all.seal.into())
+            .build()
+    }
+}
+
+impl From<basic::SighashAllOnly> for SighashAllOnly {
+    fn from(sighash_all_only: basic::SighashAllOnly) -> Self {
+        Self {
+            seal: sighash_all_only.seal().into(),
+        }
+    }
+}
+
+impl From<SighashAllOnly> for basic::SighashAllOnly {
+    fn from(sighash_all_only: SighashAllOnly) -> Self {
+        basic::SighashAllOnly::new_builder()
+            .seal(sighash_all_only.seal.into())
+            .build()
+    }
+}
+
+impl From<basic::SealPair> for SealPair {
+    fn from(seal_pair: basic::SealPair) -> Self {
+        Self {
+            script_hash: seal_pair.script_hash().unpack(),
+            seal: seal_pair.seal().into(),
+        }
+    }
+}
+
+impl From<SealPair> for basic::SealPair {
+    fn from(seal_pair: SealPair) -> Self {
+        basic::SealPair::new_builder()
+            .script_hash(seal_pair.script_hash.pack())
+            .seal(seal_pair.seal.into())
+            .build()
+    }
+}
+
+impl From<basic::OtxStart> for OtxStart {
+    fn from(otx_start: basic::OtxStart) -> Self {
+        let start_input_cell: u32 = otx_start.start_input_cell().unpack();
+        let start_output_cell: u32 = otx_start.start_output_cell().unpack();
+        let start_cell_deps: u32 = otx_start.start_cell_deps().unpack();
+        let start_header_deps: u32 = otx_start.start_header_deps().unpack();
+        Self {
+            start_input_cell: start_input_cell.into(),
+            start_output_cell: start_output_cell.into(),
+            start_cell_deps: start_cell_deps.into(),
+            start_header_deps: start_header_deps.into(),
+        }
+    }
+}
+
+impl From<OtxStart> for basic::OtxStart {
+    fn from(otx_start: OtxStart) -> Self {
+        basic::OtxStart::new_builder()
+            .start_input_cell(mol_uint32(otx_start.start_input_cell))
+            .start_output_cell(mol_uint32(otx_start.start_output_cell))
+            .start_cell_deps(mol_uint32(otx_start.start_cell_deps))
+            .start_header_deps(mol_uint32(otx_start.start_header_deps))
+            .build()
+    }
+}
+
+impl From<basic::Otx> for Otx {
+    fn from(otx: basic::Otx) -> Self {
+        let input_cells: u32 = otx.input_cells().unpack();
+        let output_cells: u32 = otx.output_cells().unpack();
+        let cell_deps: u32 = otx.cell_deps().unpack();
+        let header_deps: u32 = otx.header_deps().unpack();
+        Self {
+            input_cells: input_cells.into(),
+            output_cells: output_cells.into(),
+            cell_deps: cell_deps.into(),
+            header_deps: header_deps.into(),
+            message: otx.message().into(),
+            seals: otx.seals().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Otx> for basic::Otx {
+    fn from(otx: Otx) -> Self {
+        basic::Otx::new_builder()
+            .input_cells(mol_uint32(otx.input_cells))
+            .output_cells(mol_uint32(otx.output_cells))
+            .cell_deps(mol_uint32(otx.cell_deps))
+            .header_deps(mol_uint32(otx.header_deps))
+            .message(otx.message.into())
+            .seals(
+                basic::SealPairVec::new_builder()
+                    .set(otx.seals.into_iter().map(Into::into).collect())
+                    .build(),
+            )
+            .build()
+    }
+}
+
+impl From<top_level::WitnessLayout> for WitnessLayout {
+    fn from(layout: top_level::WitnessLayout) -> Self {
+        match layout.to_enum() {
+            top_level::WitnessLayoutUnion::SighashAll(s) => WitnessLayout::SighashAll(s.into()),
+            top_level::WitnessLayoutUnion::SighashAllOnly(s) => {
+                WitnessLayout::SighashAllOnly(s.into())
+            }
+            top_level::WitnessLayoutUnion::Otx(otx) => WitnessLayout::Otx(otx.into()),
+            top_level::WitnessLayoutUnion::OtxStart(otx_start) => {
+                WitnessLayout::OtxStart(otx_start.into())
+            }
+        }
+    }
+}
+
+impl From<WitnessLayout> for top_level::WitnessLayout {
+    fn from(layout: WitnessLayout) -> Self {
+        let layout = match layout {
+            WitnessLayout::SighashAll(s) => top_level::WitnessLayoutUnion::SighashAll(s.into()),
+            WitnessLayout::SighashAllOnly(s) => {
+                top_level::WitnessLayoutUnion::SighashAllOnly(s.into())
+            }
+            WitnessLayout::Otx(otx) => top_level::WitnessLayoutUnion::Otx(otx.into()),
+            WitnessLayout::OtxStart(otx_start) => {
+                top_level::WitnessLayoutUnion::OtxStart(otx_start.into())
+            }
+        };
+        top_level::WitnessLayout::new_builder().set(layout).build()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    blake2b::new_default_blake2b,
+    classify::{classify_witness, WitnessKind},
+    parse_message,
+    syscalls::{load_tx_hash, load_witness},
+    tx_offsets::TxOffsets,
+    Error,
+};
+use alloc::vec::Vec;
+use ckb_std::{ckb_constants::Source, error::SysError, high_level::QueryIter};
+
+///
+/// parse transaction with the legacy WitnessArgs layout, the same way as
+/// secp256k1_blake160_sighash_all in system scripts, and return 2 values:
+/// 1. signing_message_hash, 32 bytes message for signature verification
+/// 2. seal, lock field of the first WitnessArgs in current script group
+///
+/// The signing_message_hash is calculated using the following components:
+/// - transaction hash
+/// - the first witness of script group, with lock field filled by zeros
+/// - other witnesses of script group
+/// - witnesses with index beyond input cell length
+/// every witness is hashed with its length (8 bytes, little endian) ahead.
+/// This function is mainly used by lock script
+///
+pub fn parse_legacy_message() -> Result<([u8; 32], Vec<u8>), Error> {
+    let mut witness = load_witness(0, Source::GroupInput)?;
+    let lock = match classify_witness(&witness) {
+        WitnessKind::WitnessArgs {
+            lock: Some(lock), ..
+        } => lock,
+        _ => return Err(Error::MoleculeEncoding),
+    };
+    let seal = witness[lock.clone()].to_vec();
+    witness[lock].fill(0);
+
+    let mut hasher = new_default_blake2b();
+    // tx hash
+    hasher.update(&load_tx_hash()?);
+    // witnesses of script group
+    hasher.update(&(witness.len() as u64).to_le_bytes());
+    hasher.update(&witness);
+    for witness in QueryIter::new(load_witness, Source::GroupInput).skip(1) {
+        hasher.update(&(witness.len() as u64).to_le_bytes());
+        hasher.update(&witness);
+    }
+    // extra witnesses
+    let inputs_len = TxOffsets::load()?.inputs_len();
+    for witness in QueryIter::new(load_witness, Source::Input).skip(inputs_len) {
+        hasher.update(&(witness.len() as u64).to_le_bytes());
+        hasher.update(&witness);
+    }
+
+    let mut result = [0u8; 32];
+    hasher.finalize(&mut result);
+    Ok((result, seal))
+}
+
+///
+/// parse transaction in whichever layout current script group is using:
+/// `parse_message` if the first witness of script group is SighashAll or
+/// SighashAllOnly, otherwise `parse_legacy_message`
+/// This function is mainly used by lock script
+///
+pub fn parse_message_or_legacy() -> Result<([u8; 32], Vec<u8>), Error> {
+    let witness = match load_witness(0, Source::GroupInput) {
+        Ok(witness) => witness,
+        Err(SysError::IndexOutOfBound) => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if classify_witness(&witness).is_sighash() {
+        parse_message()
+    } else {
+        parse_legacy_message()
+    }
+}
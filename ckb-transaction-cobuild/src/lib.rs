@@ -1,30 +1,57 @@
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
+mod action;
 pub mod blake2b;
+#[cfg(feature = "std")]
+pub mod building_packet;
+mod classify;
+pub mod digest;
+#[cfg(feature = "std")]
+pub mod host;
+#[cfg(feature = "serde")]
+pub mod json;
+mod legacy;
+#[cfg(feature = "native")]
+pub mod native;
+mod otx;
+#[cfg(feature = "std")]
+pub mod packet_verifier;
+#[cfg(feature = "std")]
+pub mod schema_decoder;
 pub mod schemas;
+pub mod syscalls;
+#[cfg(all(test, feature = "native"))]
+mod tests;
+mod tx_offsets;
+mod validator;
 
-use alloc::vec::Vec;
-use blake2b::{new_otx_blake2b, new_sighash_all_blake2b, new_sighash_all_only_blake2b};
-use ckb_gen_types::prelude::Unpack;
-use ckb_std::{
-    ckb_constants::Source,
-    ckb_types::packed::{CellInput, Transaction},
-    error::SysError,
-    high_level::{
-        self, load_cell, load_cell_data, load_cell_lock_hash, load_tx_hash, load_witness, QueryIter,
-    },
-    syscalls::load_transaction,
+pub use action::{fetch_message_witness, parse_spore_action, ActionIter, MessageWitness};
+pub use classify::{
+    classify_current_transaction, classify_witness, classify_witnesses, WitnessKind,
 };
+pub use legacy::{parse_legacy_message, parse_message_or_legacy};
+pub use otx::{
+    fetch_non_otx_ranges, fetch_otx_batch, fetch_otx_messages, parse_otx_message,
+    verify_non_otx_inputs, verify_otx_message, NonOtxRanges, OtxBatch, OtxMessageIter, OtxMessages,
+    OtxRanges,
+};
+pub use validator::{
+    validate_current_transaction, validate_witnesses, ValidationReport, Violation, WitnessValidator,
+};
+
+use alloc::{format, vec::Vec};
+use ckb_std::{ckb_constants::Source, error::SysError, high_level::QueryIter};
 use core::convert::Into;
+use digest::DigestHasher;
 use molecule::{
     error::VerificationError,
     prelude::{Entity, Reader},
-    NUMBER_SIZE,
 };
-use schemas::{
-    basic::{Message, OtxStart, SealPairVec},
-    top_level::{WitnessLayoutReader, WitnessLayoutUnionReader},
+use schemas::basic::Message;
+use syscalls::{
+    load_cell, load_cell_data, load_cell_lock_hash, load_script_hash, load_tx_hash, load_witness,
 };
+use tx_offsets::TxOffsets;
 
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
 pub enum Error {
@@ -33,6 +60,17 @@ pub enum Error {
     WrongSighashAll,
     WrongWitnessLayout,
     WrongOtxStart,
+    /// the start_* fields of OtxStart are beyond the lengths of the transaction
+    OtxStartOutOfBound,
+    /// the Otx at this index (0 for the first Otx after OtxStart) covers input cells,
+    /// output cells, cell deps or header deps beyond the transaction
+    OtxOutOfBound(usize),
+    /// the verifier passed to `cobuild_entry` rejects a seal
+    AuthError,
+    /// no action matches the script_hash (and script_info_hash)
+    NoMatchedAction,
+    /// more than one action match the script_hash (and script_info_hash)
+    MultipleMatchedActions,
 }
 
 impl From<SysError> for Error {
@@ -47,22 +85,22 @@ impl From<VerificationError> for Error {
     }
 }
 
+///
+/// parse the seal field of a witness in SighashAll or SighashAllOnly layout
+///
+pub(crate) fn parse_seal(witness: &[u8]) -> Result<Vec<u8>, Error> {
+    match classify_witness(witness).seal() {
+        Some(seal) => Ok(witness[seal].to_vec()),
+        None => Err(Error::MoleculeEncoding),
+    }
+}
+
 ///
 /// fetch the seal field of SighashAll or SighashAllOnly in current script group
 ///
 fn fetch_seal() -> Result<Vec<u8>, Error> {
     match load_witness(0, Source::GroupInput) {
-        Ok(witness) => {
-            if let Ok(r) = WitnessLayoutReader::from_slice(&witness) {
-                match r.to_enum() {
-                    WitnessLayoutUnionReader::SighashAll(s) => Ok(s.seal().raw_data().to_vec()),
-                    WitnessLayoutUnionReader::SighashAllOnly(s) => Ok(s.seal().raw_data().to_vec()),
-                    _ => Err(Error::MoleculeEncoding),
-                }
-            } else {
-                Err(Error::MoleculeEncoding)
-            }
-        }
+        Ok(witness) => parse_seal(&witness),
         Err(e) => Err(e.into()),
     }
 }
@@ -72,20 +110,7 @@ fn fetch_seal() -> Result<Vec<u8>, Error> {
 /// returns None if there is no SighashAll witness
 /// returns Error::WrongWitnessLayout if there are more than one SighashAll witness
 pub fn fetch_message() -> Result<Option<Message>, Error> {
-    let mut iter = QueryIter::new(load_witness, Source::Input).filter_map(|witness| {
-        WitnessLayoutReader::from_slice(&witness)
-            .ok()
-            .and_then(|r| match r.to_enum() {
-                WitnessLayoutUnionReader::SighashAll(s) => Some(s.message().to_entity()),
-                _ => None,
-            })
-    });
-
-    match (iter.next(), iter.next()) {
-        (Some(message), None) => Ok(Some(message)),
-        (None, None) => Ok(None),
-        _ => Err(Error::WrongWitnessLayout),
-    }
+    Ok(fetch_message_witness()?.map(|witness| witness.message().to_entity()))
 }
 
 ///
@@ -103,56 +128,87 @@ fn check_others_in_group() -> Result<(), Error> {
     }
 }
 
-fn generate_signing_message_hash(message: &Option<Message>) -> Result<[u8; 32], Error> {
+pub(crate) fn generate_signing_message_hash(message: &Option<Message>) -> Result<[u8; 32], Error> {
     // message
     let mut hasher = match message {
         Some(m) => {
-            let mut hasher = new_sighash_all_blake2b();
-            hasher.update(m.as_slice());
+            let mut hasher = DigestHasher::sighash_all(TRACE_DIGEST);
+            hasher.component(|| "message".into(), m.as_slice());
             hasher
         }
-        None => new_sighash_all_only_blake2b(),
+        None => DigestHasher::sighash_all_only(TRACE_DIGEST),
     };
     // tx hash
-    hasher.update(&load_tx_hash()?);
+    hasher.component(|| "tx hash".into(), &load_tx_hash()?);
     // inputs cell and data
-    let inputs_len = calculate_inputs_len()?;
+    let inputs_len = TxOffsets::load()?.inputs_len();
     for i in 0..inputs_len {
         let input_cell = load_cell(i, Source::Input)?;
-        hasher.update(input_cell.as_slice());
-        // TODO cell data may be too large, use high_level::load_data fn to load and hash it in chunks
-        let input_cell_data = load_cell_data(i, Source::Input)?;
-        hasher.update(&(input_cell_data.len() as u32).to_le_bytes());
-        hasher.update(&input_cell_data);
+        hasher.component(|| format!("input cell {}", i), input_cell.as_slice());
+        hash_cell_data(&mut hasher, "input", i, Source::Input)?;
     }
     // extra witnesses
-    for witness in QueryIter::new(load_witness, Source::Input).skip(inputs_len) {
-        hasher.update(&(witness.len() as u32).to_le_bytes());
-        hasher.update(&witness);
+    for (i, witness) in QueryIter::new(load_witness, Source::Input)
+        .enumerate()
+        .skip(inputs_len)
+    {
+        hasher.component(
+            || format!("witness length {}", i),
+            &(witness.len() as u32).to_le_bytes(),
+        );
+        hasher.component(|| format!("witness {}", i), &witness);
     }
 
-    let mut result = [0u8; 32];
-    hasher.finalize(&mut result);
-    Ok(result)
+    let trace = hasher.finalize();
+    #[cfg(feature = "debug")]
+    trace.log();
+    Ok(trace.digest)
 }
 
+/// trace the components of signing message hashes and log them, see `digest`
+pub(crate) const TRACE_DIGEST: bool = cfg!(feature = "debug");
+
+/// size of the buffer used to load cell data partially
+const CHUNK_SIZE: usize = 32 * 1024;
+
 ///
-/// the molecule data structure of transaction is:
-/// full-size|raw-offset|witnesses-offset|raw-full-size|version-offset|cell_deps-offset|header_deps-offset|inputs-offset|outputs-offset|...
-/// full-size and offset are 4 bytes, so we can read the inputs-offset and outputs-offset at [28, 36),
-/// then we can get the length of inputs by calculating the difference between inputs-offset and outputs-offset
+/// hash the length of cell data (4 bytes, little endian) and then the data itself.
+/// The data is loaded in chunks of `CHUNK_SIZE` bytes via partial loading, so
+/// it is never held in memory as a whole.
 ///
-fn calculate_inputs_len() -> Result<usize, SysError> {
-    let mut offsets = [0u8; 8];
-    match load_transaction(&mut offsets, 28) {
-        // this syscall will always return SysError::LengthNotEnough since we only load 8 bytes, let's ignore it
-        Err(SysError::LengthNotEnough(_)) => {}
-        Err(SysError::Unknown(e)) => return Err(SysError::Unknown(e)),
-        _ => unreachable!(),
+pub(crate) fn hash_cell_data(
+    hasher: &mut DigestHasher,
+    kind: &str,
+    index: usize,
+    source: Source,
+) -> Result<(), Error> {
+    let mut buf = [0u8; CHUNK_SIZE];
+    // the first load tells the full length of cell data
+    let data_len = match load_cell_data(&mut buf, 0, index, source) {
+        Ok(len) => len,
+        Err(SysError::LengthNotEnough(len)) => len,
+        Err(e) => return Err(e.into()),
+    };
+    hasher.component(
+        || format!("{} cell data length {}", kind, index),
+        &(data_len as u32).to_le_bytes(),
+    );
+
+    hasher.begin(|| format!("{} cell data {}", kind, index));
+    let mut offset = 0;
+    loop {
+        let loaded = core::cmp::min(data_len - offset, CHUNK_SIZE);
+        hasher.update(&buf[..loaded]);
+        offset += loaded;
+        if offset >= data_len {
+            break;
+        }
+        match load_cell_data(&mut buf, offset, index, source) {
+            Ok(_) | Err(SysError::LengthNotEnough(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
     }
-    let inputs_offset = u32::from_le_bytes(offsets[0..4].try_into().unwrap());
-    let outputs_offset = u32::from_le_bytes(offsets[4..8].try_into().unwrap());
-    Ok((outputs_offset as usize - inputs_offset as usize - NUMBER_SIZE) / CellInput::TOTAL_SIZE)
+    Ok(())
 }
 
 ///
@@ -169,211 +225,62 @@ pub fn parse_message() -> Result<([u8; 32], Vec<u8>), Error> {
     Ok((signing_message_hash, seal))
 }
 
-/// OtxMessageIter is an iterator over the otx message in current transaction
-/// The item of this iterator is a tuple of signing_message_hash and SealPairVec
-pub struct OtxMessageIter {
-    tx: Transaction,
-    current_script_hash: [u8; 32],
-    witness_counter: usize,
-    input_cell_counter: usize,
-    output_cell_counter: usize,
-    cell_deps_counter: usize,
-    header_deps_counter: usize,
-}
-
-impl Iterator for OtxMessageIter {
-    type Item = ([u8; 32], SealPairVec);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let witness_iter = self.tx.witnesses().into_iter().skip(self.witness_counter);
-        let raw_tx = self.tx.raw();
-        for witness in witness_iter {
-            if let Ok(r) = WitnessLayoutReader::from_slice(&witness.raw_data()) {
-                match r.to_enum() {
-                    WitnessLayoutUnionReader::Otx(otx) => {
-                        self.witness_counter += 1;
-                        let input_cells: u32 = otx.input_cells().unpack();
-                        let output_cells: u32 = otx.output_cells().unpack();
-                        let cell_deps: u32 = otx.cell_deps().unpack();
-                        let header_deps: u32 = otx.header_deps().unpack();
-                        let mut input_lock_hash_iter =
-                            QueryIter::new(load_cell_lock_hash, Source::Input)
-                                .skip(self.input_cell_counter)
-                                .take(input_cells as usize);
-                        if input_lock_hash_iter
-                            .any(|lock_hash| lock_hash == self.current_script_hash)
-                        {
-                            let mut hasher = new_otx_blake2b();
-                            // message
-                            hasher.update(otx.message().as_slice());
-
-                            // otx inputs
-                            hasher.update(&input_cells.to_le_bytes());
-                            let input_iter = raw_tx
-                                .inputs()
-                                .into_iter()
-                                .skip(self.input_cell_counter)
-                                .zip(
-                                    QueryIter::new(load_cell, Source::Input)
-                                        .skip(self.input_cell_counter),
-                                )
-                                .zip(
-                                    QueryIter::new(load_cell_data, Source::Input)
-                                        .skip(self.input_cell_counter),
-                                );
-                            for ((input, input_cell), input_cell_data) in
-                                input_iter.take(input_cells as usize)
-                            {
-                                hasher.update(input.as_slice());
-                                hasher.update(input_cell.as_slice());
-                                hasher.update(&(input_cell_data.len() as u32).to_le_bytes());
-                                hasher.update(&input_cell_data);
-                            }
-                            self.input_cell_counter += input_cells as usize;
-
-                            // otx outputs
-                            hasher.update(&output_cells.to_le_bytes());
-                            let output_iter = raw_tx
-                                .outputs()
-                                .into_iter()
-                                .skip(self.output_cell_counter)
-                                .zip(
-                                    raw_tx
-                                        .outputs_data()
-                                        .into_iter()
-                                        .skip(self.output_cell_counter),
-                                );
-                            for (output_cell, output_cell_data) in
-                                output_iter.take(output_cells as usize)
-                            {
-                                hasher.update(output_cell.as_slice());
-                                // according to the spec, we need to hash the output data length first in little endian, then the data itself.
-                                // we are using molecule serialized slice directly here, it's same as the spec.
-                                hasher.update(output_cell_data.as_slice());
-                            }
-                            self.output_cell_counter += output_cells as usize;
-
-                            // otx cell deps
-                            hasher.update(&cell_deps.to_le_bytes());
-                            let cell_dep_iter =
-                                raw_tx.cell_deps().into_iter().skip(self.cell_deps_counter);
-                            for cell_dep in cell_dep_iter.take(cell_deps as usize) {
-                                hasher.update(cell_dep.as_slice());
-                            }
-                            self.cell_deps_counter += cell_deps as usize;
-
-                            // otx header deps
-                            hasher.update(&header_deps.to_le_bytes());
-                            let header_dep_iter = raw_tx
-                                .header_deps()
-                                .into_iter()
-                                .skip(self.header_deps_counter);
-                            for header_dep in header_dep_iter.take(header_deps as usize) {
-                                hasher.update(header_dep.as_slice());
-                            }
-                            self.header_deps_counter += header_deps as usize;
-
-                            let mut result = [0u8; 32];
-                            hasher.finalize(&mut result);
-                            return Some((result, otx.seals().to_entity()));
-                        } else {
-                            self.input_cell_counter += input_cells as usize;
-                            self.output_cell_counter += output_cells as usize;
-                            self.cell_deps_counter += cell_deps as usize;
-                            self.header_deps_counter += header_deps as usize;
-                        }
-                    }
-                    _ => return None,
-                }
-            } else {
-                return None;
-            }
-        }
-
-        None
-    }
-}
-
 ///
-/// verify all otx messages with the given script hash and verify function
-/// This function is mainly used by lock script
+/// check if the first witness in current script group is SighashAll or SighashAllOnly
 ///
-pub fn verify_otx_message<F: Fn(&[u8], &[u8; 32]) -> bool>(
-    current_script_hash: [u8; 32],
-    verify: F,
-) -> Result<bool, Error> {
-    let mut otx_message_iter = parse_otx_message(current_script_hash)?;
-    let verified = otx_message_iter.all(|(message_digest, seals)| {
-        seals
-            .into_iter()
-            .filter(|seal_pair| {
-                seal_pair.script_hash().as_slice() == current_script_hash.as_slice()
-            })
-            .any(|seal_pair| verify(&seal_pair.seal().raw_data(), &message_digest))
-    });
-    Ok(verified)
+fn group_uses_sighash() -> Result<bool, Error> {
+    match load_witness(0, Source::GroupInput) {
+        Ok(witness) => Ok(classify_witness(&witness).is_sighash()),
+        Err(SysError::IndexOutOfBound) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
 }
 
 ///
-/// parse transaction and return `OtxMessageIter`
-/// This function is mainly used by lock script
+/// the single entry of lock script supporting transaction cobuild. It works
+/// out which mode the current script group is using and passes every
+/// signing_message_hash with its seal to `verify`:
+/// 1. there is an otx batch in the transaction: the input cells in the batch
+///    are verified with otx messages, the others with SighashAll or
+///    SighashAllOnly message. See `verify_otx_message` and `verify_non_otx_inputs`.
+/// 2. no otx batch: the first witness of the script group is SighashAll or
+///    SighashAllOnly. See `parse_message`.
 ///
-pub fn parse_otx_message(current_script_hash: [u8; 32]) -> Result<OtxMessageIter, Error> {
-    let (otx_start, start_index) = fetch_otx_start()?;
-    let start_input_cell: u32 = otx_start.start_input_cell().unpack();
-    let start_output_cell: u32 = otx_start.start_output_cell().unpack();
-    let start_cell_deps: u32 = otx_start.start_cell_deps().unpack();
-    let start_header_deps: u32 = otx_start.start_header_deps().unpack();
-
-    let tx = high_level::load_transaction()?;
-
-    Ok(OtxMessageIter {
-        tx,
-        current_script_hash,
-        witness_counter: start_index + 1,
-        input_cell_counter: start_input_cell as usize,
-        output_cell_counter: start_output_cell as usize,
-        cell_deps_counter: start_cell_deps as usize,
-        header_deps_counter: start_header_deps as usize,
-    })
-}
-
-fn fetch_otx_start() -> Result<(OtxStart, usize), Error> {
-    let mut otx_start = None;
-    let mut start_index = 0;
-    let mut end_index = 0;
-
-    for (i, witness) in QueryIter::new(load_witness, Source::Input).enumerate() {
-        if let Ok(r) = WitnessLayoutReader::from_slice(&witness) {
-            match r.to_enum() {
-                WitnessLayoutUnionReader::OtxStart(o) => {
-                    if otx_start.is_none() {
-                        otx_start = Some(o.to_entity());
-                        start_index = i;
-                        end_index = i;
-                    } else {
-                        return Err(Error::WrongWitnessLayout);
-                    }
-                }
-                WitnessLayoutUnionReader::Otx(_) => {
-                    if otx_start.is_none() {
-                        return Err(Error::WrongWitnessLayout);
-                    } else {
-                        if end_index + 1 != i {
-                            return Err(Error::WrongWitnessLayout);
-                        } else {
-                            end_index = i;
-                        }
-                    }
+/// The layout of all witnesses is validated first, see `validate_current_transaction`.
+/// Returns Ok(true) if all seals are verified, Ok(false) if the script group
+/// doesn't use WitnessLayout (e.g. the traditional WitnessArgs is used) and
+/// Err(Error::AuthError) if any seal is rejected by `verify`.
+///
+pub fn cobuild_entry<F: Fn(&[u8], &[u8; 32]) -> bool>(verify: F) -> Result<bool, Error> {
+    validate_current_transaction()?.check()?;
+    let verified = match fetch_otx_batch() {
+        Ok(batch) => {
+            let current_script_hash = load_script_hash()?;
+            let mut in_otx = false;
+            for i in batch.ranges.input_cells.clone() {
+                if load_cell_lock_hash(i, Source::Input)? == current_script_hash {
+                    in_otx = true;
+                    break;
                 }
-                _ => {}
             }
+            if !in_otx && !group_uses_sighash()? {
+                return Ok(false);
+            }
+            verify_otx_message(&batch, current_script_hash, &verify)?
+                && verify_non_otx_inputs(&batch, current_script_hash, &verify)?
         }
-    }
-    if let Some(otx_start) = otx_start {
-        if end_index > 0 {
-            return Ok((otx_start, start_index));
+        Err(Error::WrongOtxStart) => {
+            if !group_uses_sighash()? {
+                return Ok(false);
+            }
+            let (signing_message_hash, seal) = parse_message()?;
+            verify(&seal, &signing_message_hash)
         }
+        Err(e) => return Err(e),
+    };
+    if verified {
+        Ok(true)
+    } else {
+        Err(Error::AuthError)
     }
-    Err(Error::WrongOtxStart)
 }
@@ -0,0 +1,213 @@
+//! An in-memory `Syscalls` backend: a transaction with its resolved input
+//! cells, run as one of its script groups.
+
+use crate::{
+    host::{ckb_hash, tx_hash},
+    schemas::{
+        basic::ResolvedInputs,
+        blockchain::{Bytes, CellOutput, Transaction},
+    },
+    syscalls::{with_syscalls, Syscalls},
+};
+use ckb_std::{ckb_constants::Source, error::SysError};
+use molecule::prelude::Entity;
+
+/// ScriptGroupType tells whether the current script runs as the lock or the
+/// type of its script group
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptGroupType {
+    Lock,
+    Type,
+}
+
+/// NativeTransaction answers the syscalls from a transaction and its resolved
+/// input cells, as the script with `script_hash`. Cell deps are not resolved,
+/// loading them returns `SysError::IndexOutOfBound`.
+#[derive(Clone)]
+pub struct NativeTransaction {
+    tx: Transaction,
+    resolved_inputs: ResolvedInputs,
+    script_hash: [u8; 32],
+    group_type: ScriptGroupType,
+}
+
+///
+/// copy `data` from `offset` into `buf`, the same as partial loading in ckb-vm
+///
+fn load_partial(data: &[u8], buf: &mut [u8], offset: usize) -> Result<usize, SysError> {
+    let data = &data[offset.min(data.len())..];
+    let len = data.len().min(buf.len());
+    buf[..len].copy_from_slice(&data[..len]);
+    if data.len() > buf.len() {
+        Err(SysError::LengthNotEnough(data.len()))
+    } else {
+        Ok(data.len())
+    }
+}
+
+impl NativeTransaction {
+    ///
+    /// run as the lock script of the first input cell by default
+    ///
+    pub fn new(tx: Transaction, resolved_inputs: ResolvedInputs) -> Self {
+        let script_hash = resolved_inputs
+            .outputs()
+            .get(0)
+            .map(|cell| ckb_hash(cell.lock().as_slice()))
+            .unwrap_or_default();
+        Self {
+            tx,
+            resolved_inputs,
+            script_hash,
+            group_type: ScriptGroupType::Lock,
+        }
+    }
+
+    ///
+    /// run as the lock script with `script_hash`
+    ///
+    pub fn lock_group(mut self, script_hash: [u8; 32]) -> Self {
+        self.script_hash = script_hash;
+        self.group_type = ScriptGroupType::Lock;
+        self
+    }
+
+    ///
+    /// run as the type script with `script_hash`
+    ///
+    pub fn type_group(mut self, script_hash: [u8; 32]) -> Self {
+        self.script_hash = script_hash;
+        self.group_type = ScriptGroupType::Type;
+        self
+    }
+
+    ///
+    /// run `f` with the library taking syscalls from this transaction
+    ///
+    pub fn run<R, F: FnOnce() -> R>(self, f: F) -> R {
+        with_syscalls(self, f)
+    }
+
+    fn in_group(&self, cell: &CellOutput) -> bool {
+        let script = match self.group_type {
+            ScriptGroupType::Lock => Some(cell.lock()),
+            ScriptGroupType::Type => cell.type_().to_opt(),
+        };
+        script.map_or(false, |script| {
+            ckb_hash(script.as_slice()) == self.script_hash
+        })
+    }
+
+    ///
+    /// the index in inputs or outputs of the `index`-th cell of current
+    /// script group, or the `index` itself for other sources
+    ///
+    fn locate(&self, index: usize, source: Source) -> Result<(usize, Source), SysError> {
+        let group_index = |cells: &mut dyn Iterator<Item = CellOutput>| {
+            cells
+                .enumerate()
+                .filter(|(_, cell)| self.in_group(cell))
+                .nth(index)
+                .map(|(i, _)| i)
+                .ok_or(SysError::IndexOutOfBound)
+        };
+        match source {
+            Source::GroupInput => Ok((
+                group_index(&mut self.resolved_inputs.outputs().into_iter())?,
+                Source::Input,
+            )),
+            Source::GroupOutput => {
+                if self.group_type == ScriptGroupType::Lock {
+                    return Err(SysError::IndexOutOfBound);
+                }
+                Ok((
+                    group_index(&mut self.tx.raw().outputs().into_iter())?,
+                    Source::Output,
+                ))
+            }
+            _ => Ok((index, source)),
+        }
+    }
+
+    fn cell(&self, index: usize, source: Source) -> Result<(CellOutput, Bytes), SysError> {
+        let (index, source) = self.locate(index, source)?;
+        let found = match source {
+            Source::Input => self
+                .resolved_inputs
+                .outputs()
+                .get(index)
+                .zip(self.resolved_inputs.outputs_data().get(index)),
+            Source::Output => self
+                .tx
+                .raw()
+                .outputs()
+                .get(index)
+                .zip(self.tx.raw().outputs_data().get(index)),
+            _ => None,
+        };
+        found.ok_or(SysError::IndexOutOfBound)
+    }
+}
+
+impl Syscalls for NativeTransaction {
+    fn load_tx_hash(&self) -> Result<[u8; 32], SysError> {
+        Ok(tx_hash(&self.tx))
+    }
+
+    fn load_script_hash(&self) -> Result<[u8; 32], SysError> {
+        Ok(self.script_hash)
+    }
+
+    fn load_transaction(&self, buf: &mut [u8], offset: usize) -> Result<usize, SysError> {
+        load_partial(self.tx.as_slice(), buf, offset)
+    }
+
+    fn load_witness(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+        let (index, source) = self.locate(index, source)?;
+        match source {
+            // witnesses are loaded by the index of input or output cells
+            Source::Input | Source::Output => self
+                .tx
+                .witnesses()
+                .get(index)
+                .map(|witness| witness.raw_data().to_vec())
+                .ok_or(SysError::IndexOutOfBound),
+            _ => Err(SysError::IndexOutOfBound),
+        }
+    }
+
+    fn load_cell(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+        self.cell(index, source)
+            .map(|(cell, _)| cell.as_slice().to_vec())
+    }
+
+    fn load_input(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+        let (index, source) = self.locate(index, source)?;
+        match source {
+            Source::Input => self
+                .tx
+                .raw()
+                .inputs()
+                .get(index)
+                .map(|input| input.as_slice().to_vec())
+                .ok_or(SysError::IndexOutOfBound),
+            _ => Err(SysError::IndexOutOfBound),
+        }
+    }
+
+    fn load_cell_lock_hash(&self, index: usize, source: Source) -> Result<[u8; 32], SysError> {
+        self.cell(index, source)
+            .map(|(cell, _)| ckb_hash(cell.lock().as_slice()))
+    }
+
+    fn load_cell_data(
+        &self,
+        buf: &mut [u8],
+        offset: usize,
+        index: usize,
+        source: Source,
+    ) -> Result<usize, SysError> {
+        let (_, data) = self.cell(index, source)?;
+        load_partial(&data.raw_data(), buf, offset)
+    }
+}
@@ -0,0 +1,514 @@
+use crate::{
+    digest::DigestHasher,
+    fetch_message, generate_signing_message_hash, hash_cell_data, parse_seal,
+    schemas::{
+        basic::{Message, OtxReader, OtxStart, SealPairVec},
+        top_level::{WitnessLayoutReader, WitnessLayoutUnionReader},
+    },
+    syscalls::{load_cell, load_cell_lock_hash, load_input, load_witness},
+    tx_offsets::TxOffsets,
+    Error, TRACE_DIGEST,
+};
+use alloc::{format, vec::Vec};
+use ckb_gen_types::prelude::Unpack;
+use ckb_std::{ckb_constants::Source, error::SysError, high_level::QueryIter};
+use core::ops::Range;
+use molecule::prelude::{Entity, Reader};
+
+/// Index ranges of input cells, output cells, cell deps and header deps in
+/// current transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OtxRanges {
+    pub input_cells: Range<usize>,
+    pub output_cells: Range<usize>,
+    pub cell_deps: Range<usize>,
+    pub header_deps: Range<usize>,
+}
+
+/// The otx batch in current transaction: an OtxStart witness followed by
+/// one or more Otx witnesses
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OtxBatch {
+    /// index of the OtxStart witness
+    pub start_witness: usize,
+    /// number of Otx witnesses following OtxStart
+    pub otx_count: usize,
+    /// the items covered by all otxs in the batch
+    pub ranges: OtxRanges,
+}
+
+/// Index ranges of the items outside of the otx batch, the first range is
+/// before the batch and the second one is after it. These items are normally
+/// added by the aggregator, e.g. to pay fees and collect change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonOtxRanges {
+    pub input_cells: [Range<usize>; 2],
+    pub output_cells: [Range<usize>; 2],
+    pub cell_deps: [Range<usize>; 2],
+    pub header_deps: [Range<usize>; 2],
+}
+
+///
+/// move the end of `covered` forward over `count` items and return the range passed over.
+/// Returns None if the range overflows or goes beyond `len`.
+/// It's shared with `host::otx_batch`, which walks through the otx batch off-chain.
+///
+pub(crate) fn advance(covered: &mut Range<usize>, count: u32, len: usize) -> Option<Range<usize>> {
+    let start = covered.end;
+    let end = start.checked_add(count as usize)?;
+    if end > len {
+        return None;
+    }
+    covered.end = end;
+    Some(start..end)
+}
+
+///
+/// get the Otx from a witness already verified as Otx variant of WitnessLayout
+///
+fn otx_reader(witness: &[u8]) -> OtxReader {
+    match WitnessLayoutReader::new_unchecked(witness).to_enum() {
+        WitnessLayoutUnionReader::Otx(otx) => otx,
+        _ => unreachable!(),
+    }
+}
+
+/// OtxCursor walks through the Otx witnesses following OtxStart, and tracks
+/// the items covered by the otxs passed over.
+struct OtxCursor {
+    tx_offsets: TxOffsets,
+    witness_counter: usize,
+    otx_counter: usize,
+    covered: OtxRanges,
+}
+
+impl OtxCursor {
+    fn new() -> Result<Self, Error> {
+        let (otx_start, start_index) = fetch_otx_start()?;
+        Self::start_at(otx_start, start_index)
+    }
+
+    ///
+    /// start from the OtxStart of a batch already fetched by `fetch_otx_batch`,
+    /// without scanning all witnesses again
+    ///
+    fn from_batch(batch: &OtxBatch) -> Result<Self, Error> {
+        let witness = load_witness(batch.start_witness, Source::Input)?;
+        let otx_start = match WitnessLayoutReader::from_slice(&witness).map(|r| r.to_enum()) {
+            Ok(WitnessLayoutUnionReader::OtxStart(o)) => o.to_entity(),
+            _ => return Err(Error::WrongOtxStart),
+        };
+        Self::start_at(otx_start, batch.start_witness)
+    }
+
+    fn start_at(otx_start: OtxStart, start_index: usize) -> Result<Self, Error> {
+        let start_input_cell: u32 = otx_start.start_input_cell().unpack();
+        let start_output_cell: u32 = otx_start.start_output_cell().unpack();
+        let start_cell_deps: u32 = otx_start.start_cell_deps().unpack();
+        let start_header_deps: u32 = otx_start.start_header_deps().unpack();
+
+        let tx_offsets = TxOffsets::load()?;
+        if start_input_cell as usize > tx_offsets.inputs_len()
+            || start_output_cell as usize > tx_offsets.outputs_len()
+            || start_cell_deps as usize > tx_offsets.cell_deps_len()
+            || start_header_deps as usize > tx_offsets.header_deps_len()
+        {
+            return Err(Error::OtxStartOutOfBound);
+        }
+
+        let empty_at = |start: u32| start as usize..start as usize;
+        Ok(Self {
+            tx_offsets,
+            witness_counter: start_index + 1,
+            otx_counter: 0,
+            covered: OtxRanges {
+                input_cells: empty_at(start_input_cell),
+                output_cells: empty_at(start_output_cell),
+                cell_deps: empty_at(start_cell_deps),
+                header_deps: empty_at(start_header_deps),
+            },
+        })
+    }
+
+    ///
+    /// load the next Otx witness, returns None at the end of the otx batch,
+    /// which is the first witness not in Otx variant
+    ///
+    fn next_witness(&mut self) -> Option<Vec<u8>> {
+        let witness = load_witness(self.witness_counter, Source::Input).ok()?;
+        match WitnessLayoutReader::from_slice(&witness).map(|r| r.to_enum()) {
+            Ok(WitnessLayoutUnionReader::Otx(_)) => {
+                self.witness_counter += 1;
+                Some(witness)
+            }
+            _ => None,
+        }
+    }
+
+    ///
+    /// move the cursor over the otx and return the items it covers
+    ///
+    fn advance(&mut self, otx: OtxReader) -> Result<OtxRanges, Error> {
+        let input_cells: u32 = otx.input_cells().unpack();
+        let output_cells: u32 = otx.output_cells().unpack();
+        let cell_deps: u32 = otx.cell_deps().unpack();
+        let header_deps: u32 = otx.header_deps().unpack();
+
+        let out_of_bound = Error::OtxOutOfBound(self.otx_counter);
+        self.otx_counter += 1;
+        let tx_offsets = &self.tx_offsets;
+        let covered = &mut self.covered;
+        Ok(OtxRanges {
+            input_cells: advance(
+                &mut covered.input_cells,
+                input_cells,
+                tx_offsets.inputs_len(),
+            )
+            .ok_or(out_of_bound)?,
+            output_cells: advance(
+                &mut covered.output_cells,
+                output_cells,
+                tx_offsets.outputs_len(),
+            )
+            .ok_or(out_of_bound)?,
+            cell_deps: advance(
+                &mut covered.cell_deps,
+                cell_deps,
+                tx_offsets.cell_deps_len(),
+            )
+            .ok_or(out_of_bound)?,
+            header_deps: advance(
+                &mut covered.header_deps,
+                header_deps,
+                tx_offsets.header_deps_len(),
+            )
+            .ok_or(out_of_bound)?,
+        })
+    }
+}
+
+/// OtxMessageIter is an iterator over the otx message in current transaction
+/// The item of this iterator is a tuple of signing_message_hash and SealPairVec
+///
+/// The transaction is never loaded as a whole: witnesses, inputs, outputs,
+/// outputs data, cell deps and header deps are loaded by index through
+/// (partial) syscalls, in a single forward pass over the otx batch.
+///
+/// Every Otx passed over is checked against the lengths of the transaction,
+/// an Otx claiming cells or deps which don't exist yields
+/// `Error::OtxOutOfBound` with its index in the batch.
+pub struct OtxMessageIter {
+    cursor: OtxCursor,
+    current_script_hash: [u8; 32],
+}
+
+impl OtxMessageIter {
+    ///
+    /// returns the signing message hash and seals of the otx if any of its input
+    /// cells is locked by current script, otherwise None.
+    ///
+    fn process_otx(
+        &self,
+        otx: OtxReader,
+        ranges: OtxRanges,
+    ) -> Result<Option<([u8; 32], SealPairVec)>, Error> {
+        let mut found = false;
+        for i in ranges.input_cells.clone() {
+            if load_cell_lock_hash(i, Source::Input)? == self.current_script_hash {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return Ok(None);
+        }
+
+        let mut hasher = DigestHasher::otx(TRACE_DIGEST);
+        // message
+        hasher.component(|| "message".into(), otx.message().as_slice());
+
+        // otx inputs
+        hasher.component(
+            || "input cells count".into(),
+            &(ranges.input_cells.len() as u32).to_le_bytes(),
+        );
+        for i in ranges.input_cells {
+            hasher.component(
+                || format!("input {}", i),
+                load_input(i, Source::Input)?.as_slice(),
+            );
+            hasher.component(
+                || format!("input cell {}", i),
+                load_cell(i, Source::Input)?.as_slice(),
+            );
+            hash_cell_data(&mut hasher, "input", i, Source::Input)?;
+        }
+
+        // otx outputs
+        hasher.component(
+            || "output cells count".into(),
+            &(ranges.output_cells.len() as u32).to_le_bytes(),
+        );
+        for i in ranges.output_cells {
+            hasher.component(
+                || format!("output cell {}", i),
+                load_cell(i, Source::Output)?.as_slice(),
+            );
+            // according to the spec, we need to hash the output data length first in little endian, then the data itself.
+            // it's same as the molecule serialized outputs_data item.
+            hash_cell_data(&mut hasher, "output", i, Source::Output)?;
+        }
+
+        // otx cell deps
+        hasher.component(
+            || "cell deps count".into(),
+            &(ranges.cell_deps.len() as u32).to_le_bytes(),
+        );
+        for i in ranges.cell_deps {
+            hasher.component(
+                || format!("cell dep {}", i),
+                &self.cursor.tx_offsets.load_cell_dep(i)?,
+            );
+        }
+
+        // otx header deps
+        hasher.component(
+            || "header deps count".into(),
+            &(ranges.header_deps.len() as u32).to_le_bytes(),
+        );
+        for i in ranges.header_deps {
+            hasher.component(
+                || format!("header dep {}", i),
+                &self.cursor.tx_offsets.load_header_dep(i)?,
+            );
+        }
+
+        let trace = hasher.finalize();
+        #[cfg(feature = "debug")]
+        trace.log();
+        let result = trace.digest;
+        Ok(Some((result, otx.seals().to_entity())))
+    }
+}
+
+impl Iterator for OtxMessageIter {
+    type Item = Result<([u8; 32], SealPairVec), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let witness = self.cursor.next_witness()?;
+            let otx = otx_reader(&witness);
+            let result = self
+                .cursor
+                .advance(otx)
+                .and_then(|ranges| self.process_otx(otx, ranges));
+            match result {
+                Ok(Some(item)) => return Some(Ok(item)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+///
+/// verify all otx messages in the batch with the given script hash and verify
+/// function, the batch is fetched by `fetch_otx_batch`
+/// This function is mainly used by lock script
+///
+pub fn verify_otx_message<F: Fn(&[u8], &[u8; 32]) -> bool>(
+    batch: &OtxBatch,
+    current_script_hash: [u8; 32],
+    verify: F,
+) -> Result<bool, Error> {
+    let iter = OtxMessageIter {
+        cursor: OtxCursor::from_batch(batch)?,
+        current_script_hash,
+    };
+    for item in iter {
+        let (message_digest, seals) = item?;
+        let verified = seals
+            .into_iter()
+            .filter(|seal_pair| {
+                seal_pair.script_hash().as_slice() == current_script_hash.as_slice()
+            })
+            .any(|seal_pair| verify(&seal_pair.seal().raw_data(), &message_digest));
+        if !verified {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+///
+/// parse transaction and return `OtxMessageIter`
+/// This function is mainly used by lock script
+///
+pub fn parse_otx_message(current_script_hash: [u8; 32]) -> Result<OtxMessageIter, Error> {
+    Ok(OtxMessageIter {
+        cursor: OtxCursor::new()?,
+        current_script_hash,
+    })
+}
+
+/// OtxMessages is an iterator over the otxs in current transaction, the item
+/// is the message of an otx and the items it covers. It's empty if there is
+/// no otx batch in the transaction.
+/// This iterator is mainly used by type script, to verify its actions against
+/// the cells of each otx.
+pub struct OtxMessages {
+    cursor: Option<OtxCursor>,
+}
+
+impl Iterator for OtxMessages {
+    type Item = Result<(Message, OtxRanges), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cursor = self.cursor.as_mut()?;
+        let witness = cursor.next_witness()?;
+        let otx = otx_reader(&witness);
+        let result = cursor
+            .advance(otx)
+            .map(|ranges| (otx.message().to_entity(), ranges));
+        if result.is_err() {
+            // the rest of the batch can't be located
+            self.cursor = None;
+        }
+        Some(result)
+    }
+}
+
+///
+/// fetch the message of every otx in current transaction, together with the
+/// input cells, output cells, cell deps and header deps the otx covers
+/// This function is mainly used by type script
+///
+pub fn fetch_otx_messages() -> Result<OtxMessages, Error> {
+    match OtxCursor::new() {
+        Ok(cursor) => Ok(OtxMessages {
+            cursor: Some(cursor),
+        }),
+        Err(Error::WrongOtxStart) => Ok(OtxMessages { cursor: None }),
+        Err(e) => Err(e),
+    }
+}
+
+///
+/// walk through the whole otx batch, returns the cursor at the end of the batch
+///
+fn walk_otx_batch() -> Result<OtxCursor, Error> {
+    let mut cursor = OtxCursor::new()?;
+    while let Some(witness) = cursor.next_witness() {
+        cursor.advance(otx_reader(&witness))?;
+    }
+    Ok(cursor)
+}
+
+///
+/// fetch the otx batch in current transaction, with all its otxs validated
+/// against the lengths of the transaction
+/// returns Error::WrongOtxStart if there is no otx batch
+///
+pub fn fetch_otx_batch() -> Result<OtxBatch, Error> {
+    let cursor = walk_otx_batch()?;
+    Ok(OtxBatch {
+        start_witness: cursor.witness_counter - cursor.otx_counter - 1,
+        otx_count: cursor.otx_counter,
+        ranges: cursor.covered,
+    })
+}
+
+///
+/// fetch the index ranges of input cells, output cells, cell deps and header
+/// deps which are not covered by the otx batch
+/// returns Error::WrongOtxStart if there is no otx batch
+///
+pub fn fetch_non_otx_ranges() -> Result<NonOtxRanges, Error> {
+    let cursor = walk_otx_batch()?;
+    let tx_offsets = &cursor.tx_offsets;
+    let covered = &cursor.covered;
+    let split = |covered: &Range<usize>, len: usize| [0..covered.start, covered.end..len];
+    Ok(NonOtxRanges {
+        input_cells: split(&covered.input_cells, tx_offsets.inputs_len()),
+        output_cells: split(&covered.output_cells, tx_offsets.outputs_len()),
+        cell_deps: split(&covered.cell_deps, tx_offsets.cell_deps_len()),
+        header_deps: split(&covered.header_deps, tx_offsets.header_deps_len()),
+    })
+}
+
+///
+/// verify the input cells locked by current script but outside of the otx
+/// batch. They are covered by the SighashAll (or SighashAllOnly if there is no
+/// SighashAll in the transaction) signing message hash of the whole
+/// transaction: the witness at the index of the first such input cell must be
+/// SighashAll or SighashAllOnly, the witnesses of the others must be empty.
+/// returns Ok(true) if there is no such input cell. The batch is fetched by
+/// `fetch_otx_batch`.
+/// This function is mainly used by lock script, together with `verify_otx_message`
+///
+pub fn verify_non_otx_inputs<F: Fn(&[u8], &[u8; 32]) -> bool>(
+    batch: &OtxBatch,
+    current_script_hash: [u8; 32],
+    verify: F,
+) -> Result<bool, Error> {
+    let mut seal = None;
+    for (i, lock_hash) in QueryIter::new(load_cell_lock_hash, Source::Input).enumerate() {
+        if batch.ranges.input_cells.contains(&i) || lock_hash != current_script_hash {
+            continue;
+        }
+        let witness = match load_witness(i, Source::Input) {
+            Ok(witness) => witness,
+            Err(SysError::IndexOutOfBound) => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if seal.is_none() {
+            seal = Some(parse_seal(&witness)?);
+        } else if !witness.is_empty() {
+            return Err(Error::WrongWitnessLayout);
+        }
+    }
+    match seal {
+        Some(seal) => {
+            let message_digest = generate_signing_message_hash(&fetch_message()?)?;
+            Ok(verify(&seal, &message_digest))
+        }
+        None => Ok(true),
+    }
+}
+
+fn fetch_otx_start() -> Result<(OtxStart, usize), Error> {
+    let mut otx_start = None;
+    let mut start_index = 0;
+    let mut end_index = 0;
+
+    for (i, witness) in QueryIter::new(load_witness, Source::Input).enumerate() {
+        if let Ok(r) = WitnessLayoutReader::from_slice(&witness) {
+            match r.to_enum() {
+                WitnessLayoutUnionReader::OtxStart(o) => {
+                    if otx_start.is_none() {
+                        otx_start = Some(o.to_entity());
+                        start_index = i;
+                        end_index = i;
+                    } else {
+                        return Err(Error::WrongWitnessLayout);
+                    }
+                }
+                WitnessLayoutUnionReader::Otx(_) => {
+                    if otx_start.is_none() || end_index + 1 != i {
+                        return Err(Error::WrongWitnessLayout);
+                    } else {
+                        end_index = i;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(otx_start) = otx_start {
+        // at least one Otx follows OtxStart
+        if end_index > start_index {
+            return Ok((otx_start, start_index));
+        }
+    }
+    Err(Error::WrongOtxStart)
+}
@@ -0,0 +1,80 @@
+//! The checks a wallet runs on a `BuildingPacket` received from a dApp before
+//! showing it to users. Unlike `building_packet::check`, all problems are
+//! collected, see `verify_packet`.
+
+use crate::{
+    building_packet::packet_findings,
+    schemas::basic::{BuildingPacketReader, BuildingPacketUnionReader, BuildingPacketV1Reader},
+    validate_witnesses, Error, Violation,
+};
+use molecule::prelude::Reader;
+
+/// The position of an action in a BuildingPacket
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionIndex {
+    /// the action at this index of `message.actions`
+    Message(usize),
+    /// the action at this index of `lock_actions`
+    Lock(usize),
+}
+
+/// A problem found in a BuildingPacket
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Finding {
+    /// the number of resolved inputs doesn't match the inputs of payload
+    ResolvedInputsMismatch { inputs: usize, resolved: usize },
+    /// the outputs and outputs_data of resolved inputs have different lengths
+    ResolvedInputsDataMismatch { outputs: usize, outputs_data: usize },
+    /// the change output is beyond the outputs of payload
+    ChangeOutputOutOfBound(u32),
+    /// the ScriptInfo at this index is the same as an earlier one
+    DuplicateScriptInfo(usize),
+    /// no ScriptInfo matches the script_info_hash of the action
+    MissingScriptInfo(ActionIndex),
+    /// the script_hash of the action differs from the one in its ScriptInfo
+    ScriptHashMismatch {
+        action: ActionIndex,
+        script_info: usize,
+    },
+    /// the action belongs to no script in the transaction. The lock actions
+    /// must belong to lock scripts of input cells, the message actions to lock
+    /// scripts of input cells or type scripts of input and output cells
+    UnknownScript(ActionIndex),
+    /// the witnesses already in payload break the witness layout rules
+    Witness(Violation),
+}
+
+///
+/// parse `data` as a BuildingPacket and verify it, see `verify_packet`
+///
+pub fn verify_packet_slice(data: &[u8]) -> Result<Vec<Finding>, Error> {
+    let packet = BuildingPacketReader::from_slice(data)?;
+    Ok(verify_packet(packet))
+}
+
+///
+/// verify a BuildingPacket and return all findings, empty if the packet is
+/// consistent
+///
+pub fn verify_packet(packet: BuildingPacketReader) -> Vec<Finding> {
+    match packet.to_enum() {
+        BuildingPacketUnionReader::BuildingPacketV1(packet) => verify_packet_v1(packet),
+    }
+}
+
+///
+/// verify a BuildingPacketV1 and return all findings, empty if the packet is
+/// consistent
+///
+pub fn verify_packet_v1(packet: BuildingPacketV1Reader) -> Vec<Finding> {
+    let mut findings = packet_findings(packet);
+
+    // witnesses
+    let witnesses = packet.payload().witnesses();
+    let witnesses: Vec<&[u8]> = witnesses.iter().map(|witness| witness.raw_data()).collect();
+    let inputs = packet.payload().raw().inputs().len();
+    let report = validate_witnesses(&witnesses, inputs);
+    findings.extend(report.violations.into_iter().map(Finding::Witness));
+
+    findings
+}
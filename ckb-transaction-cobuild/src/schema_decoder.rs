@@ -0,0 +1,603 @@
+//! Decode `Action.data` at runtime with the molecule schema in
+//! `ScriptInfo.schema`, so that wallets can show actions of any dApp without
+//! compiled-in bindings, see `decode_action`.
+//!
+//! The schemas in this repository (blockchain, basic, top_level and spore)
+//! are bundled, `import` statements can only refer to them. The copies in
+//! `ckb-transaction-cobuild/schemas` are updated by `make mol`, `make ci`
+//! fails if they differ from `schemas`.
+
+use crate::schemas::basic::{ActionReader, ScriptInfoReader};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ops::Range;
+
+const BUNDLED_SCHEMAS: [(&str, &str); 4] = [
+    ("blockchain", include_str!("../schemas/blockchain.mol")),
+    ("basic", include_str!("../schemas/basic.mol")),
+    ("top_level", include_str!("../schemas/top_level.mol")),
+    ("spore", include_str!("../schemas/spore.mol")),
+];
+
+/// nested tables, vectors and unions deeper than this are rejected
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// ScriptInfo.schema or ScriptInfo.message_type isn't UTF-8
+    InvalidUtf8,
+    /// the schema text can't be parsed at this line (1 based)
+    Syntax { line: usize, reason: &'static str },
+    /// the import isn't one of the bundled schemas
+    UnknownImport(String),
+    /// the type is defined more than once
+    DuplicateType(String),
+    /// the type is used but never defined
+    UnknownType(String),
+    /// the array or struct has a field or item which isn't fixed size, or
+    /// contains itself
+    NotFixedSize(String),
+    /// the array or struct is empty, e.g. `array A [byte; 0]` or `struct A {}`.
+    /// Any number of its items would fit in no data.
+    ZeroSize(String),
+    /// the data doesn't match the type
+    Malformed(String),
+    /// the item id doesn't belong to the union
+    UnknownUnionItem { union: String, item_id: u32 },
+    /// the data nests deeper than `MAX_DEPTH`
+    TooDeep,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TypeDef {
+    Byte,
+    Array { item: String, count: usize },
+    Struct { fields: Vec<(String, String)> },
+    Vector { item: String },
+    Option { item: String },
+    Union { items: Vec<(String, u32)> },
+    Table { fields: Vec<(String, String)> },
+}
+
+/// The decoded value. Byte arrays and byte vectors, such as Byte32 and Bytes,
+/// are decoded as `Bytes`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Byte(u8),
+    Bytes(Vec<u8>),
+    /// arrays and vectors of other types
+    List(Vec<Value>),
+    /// structs and tables, fields in the order of definition
+    Fields(Vec<(String, Value)>),
+    /// absent option
+    None,
+    Union {
+        item: String,
+        value: Box<Value>,
+    },
+}
+
+impl Value {
+    ///
+    /// the JSON text of the value. Bytes are hex strings with 0x prefix, unions
+    /// are objects with `type` and `value`.
+    ///
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        match self {
+            Value::Byte(byte) => write!(json, "\"0x{:02x}\"", byte).unwrap(),
+            Value::Bytes(bytes) => {
+                json.push_str("\"0x");
+                for byte in bytes {
+                    write!(json, "{:02x}", byte).unwrap();
+                }
+                json.push('"');
+            }
+            Value::List(items) => {
+                json.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    item.write_json(json);
+                }
+                json.push(']');
+            }
+            Value::Fields(fields) => {
+                json.push('{');
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    write!(json, "\"{}\":", name).unwrap();
+                    value.write_json(json);
+                }
+                json.push('}');
+            }
+            Value::None => json.push_str("null"),
+            Value::Union { item, value } => {
+                write!(json, "{{\"type\":\"{}\",\"value\":", item).unwrap();
+                value.write_json(json);
+                json.push('}');
+            }
+        }
+    }
+}
+
+/// The type definitions of a molecule schema
+#[derive(Clone, Debug)]
+pub struct Schema {
+    types: BTreeMap<String, TypeDef>,
+}
+
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, DecodeError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+            rest = &rest[1..];
+        } else if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if rest.starts_with("//") {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+        } else if rest.starts_with("/*") {
+            let end = rest.find("*/").ok_or(DecodeError::Syntax {
+                line,
+                reason: "unterminated comment",
+            })?;
+            line += rest[..end].matches('\n').count();
+            rest = &rest[end + 2..];
+        } else if "{}[]<>();:,".contains(c) {
+            tokens.push(Token {
+                text: &rest[..1],
+                line,
+            });
+            rest = &rest[1..];
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token {
+                text: &rest[..end],
+                line,
+            });
+            rest = &rest[end..];
+        } else {
+            return Err(DecodeError::Syntax {
+                line,
+                reason: "unexpected character",
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |token| token.line)
+    }
+
+    fn error<T>(&self, reason: &'static str) -> Result<T, DecodeError> {
+        Err(DecodeError::Syntax {
+            line: self.line(),
+            reason,
+        })
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(|token| token.text)
+    }
+
+    fn next_token(&mut self) -> Option<&'a str> {
+        let text = self.peek();
+        self.pos += 1;
+        text
+    }
+
+    fn expect(&mut self, text: &'static str, reason: &'static str) -> Result<(), DecodeError> {
+        if self.peek() == Some(text) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.error(reason)
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, DecodeError> {
+        match self.peek() {
+            Some(text) if text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') => {
+                self.pos += 1;
+                Ok(text.to_string())
+            }
+            _ => self.error("expected a name"),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, DecodeError> {
+        match self.peek().and_then(|text| text.parse().ok()) {
+            Some(number) => {
+                self.pos += 1;
+                Ok(number)
+            }
+            None => self.error("expected a number"),
+        }
+    }
+
+    ///
+    /// `name: Type` pairs in braces, separated by commas
+    ///
+    fn fields(&mut self) -> Result<Vec<(String, String)>, DecodeError> {
+        self.expect("{", "expected {")?;
+        let mut fields = Vec::new();
+        while self.peek() != Some("}") {
+            let name = self.ident()?;
+            self.expect(":", "expected :")?;
+            fields.push((name, self.ident()?));
+            if self.peek() != Some("}") {
+                self.expect(",", "expected , or }")?;
+            }
+        }
+        self.pos += 1;
+        Ok(fields)
+    }
+
+    ///
+    /// union items in braces, the id of an item without explicit id is the
+    /// previous one plus 1, starting from 0
+    ///
+    fn union_items(&mut self) -> Result<Vec<(String, u32)>, DecodeError> {
+        self.expect("{", "expected {")?;
+        let mut items: Vec<(String, u32)> = Vec::new();
+        while self.peek() != Some("}") {
+            let name = self.ident()?;
+            let id = if self.peek() == Some(":") {
+                self.pos += 1;
+                u32::try_from(self.number()?).or_else(|_| self.error("union item id overflow"))?
+            } else {
+                match items.last() {
+                    Some((_, id)) => id
+                        .checked_add(1)
+                        .map_or_else(|| self.error("union item id overflow"), Ok)?,
+                    None => 0,
+                }
+            };
+            if items.iter().any(|(_, other)| *other == id) {
+                return self.error("duplicate union item id");
+            }
+            items.push((name, id));
+            if self.peek() != Some("}") {
+                self.expect(",", "expected , or }")?;
+            }
+        }
+        self.pos += 1;
+        Ok(items)
+    }
+}
+
+impl Schema {
+    ///
+    /// parse the schema text, the types of the bundled schemas imported by it
+    /// are included
+    ///
+    pub fn parse(text: &str) -> Result<Self, DecodeError> {
+        let mut schema = Schema {
+            types: BTreeMap::new(),
+        };
+        schema.types.insert("byte".to_string(), TypeDef::Byte);
+        let mut imported = BTreeSet::new();
+        schema.parse_text(text, &mut imported)?;
+        schema.check()?;
+        Ok(schema)
+    }
+
+    fn parse_text(
+        &mut self,
+        text: &str,
+        imported: &mut BTreeSet<&'static str>,
+    ) -> Result<(), DecodeError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+        };
+        while let Some(keyword) = parser.next_token() {
+            if keyword == "import" {
+                let name = parser.ident()?;
+                parser.expect(";", "expected ;")?;
+                let &(name, text) = BUNDLED_SCHEMAS
+                    .iter()
+                    .find(|(bundled, _)| *bundled == name)
+                    .ok_or(DecodeError::UnknownImport(name))?;
+                if imported.insert(name) {
+                    self.parse_text(text, imported)?;
+                }
+                continue;
+            }
+            let name = parser.ident()?;
+            let def = match keyword {
+                "array" => {
+                    parser.expect("[", "expected [")?;
+                    let item = parser.ident()?;
+                    parser.expect(";", "expected ;")?;
+                    let count = parser.number()? as usize;
+                    parser.expect("]", "expected ]")?;
+                    parser.expect(";", "expected ;")?;
+                    TypeDef::Array { item, count }
+                }
+                "struct" => TypeDef::Struct {
+                    fields: parser.fields()?,
+                },
+                "vector" => {
+                    parser.expect("<", "expected <")?;
+                    let item = parser.ident()?;
+                    parser.expect(">", "expected >")?;
+                    parser.expect(";", "expected ;")?;
+                    TypeDef::Vector { item }
+                }
+                "option" => {
+                    parser.expect("(", "expected (")?;
+                    let item = parser.ident()?;
+                    parser.expect(")", "expected )")?;
+                    parser.expect(";", "expected ;")?;
+                    TypeDef::Option { item }
+                }
+                "union" => TypeDef::Union {
+                    items: parser.union_items()?,
+                },
+                "table" => TypeDef::Table {
+                    fields: parser.fields()?,
+                },
+                _ => {
+                    parser.pos -= 2;
+                    return parser.error("expected a definition or import");
+                }
+            };
+            if self.types.insert(name.clone(), def).is_some() {
+                return Err(DecodeError::DuplicateType(name));
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// every used type is defined, and arrays and structs are fixed size
+    ///
+    fn check(&self) -> Result<(), DecodeError> {
+        for (name, def) in &self.types {
+            let used: Vec<&String> = match def {
+                TypeDef::Byte => vec![],
+                TypeDef::Array { item, .. }
+                | TypeDef::Vector { item }
+                | TypeDef::Option { item } => {
+                    vec![item]
+                }
+                TypeDef::Struct { fields } | TypeDef::Table { fields } => {
+                    fields.iter().map(|(_, t)| t).collect()
+                }
+                TypeDef::Union { items } => items.iter().map(|(t, _)| t).collect(),
+            };
+            for t in used {
+                if !self.types.contains_key(t) {
+                    return Err(DecodeError::UnknownType(t.clone()));
+                }
+            }
+            if matches!(def, TypeDef::Array { .. } | TypeDef::Struct { .. }) {
+                match self.fixed_size(name, 0) {
+                    None => return Err(DecodeError::NotFixedSize(name.clone())),
+                    Some(0) => return Err(DecodeError::ZeroSize(name.clone())),
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// the size of a fixed size type, None for the others
+    ///
+    fn fixed_size(&self, name: &str, depth: usize) -> Option<usize> {
+        if depth > self.types.len() {
+            return None;
+        }
+        match self.types.get(name)? {
+            TypeDef::Byte => Some(1),
+            TypeDef::Array { item, count } => self.fixed_size(item, depth + 1)?.checked_mul(*count),
+            TypeDef::Struct { fields } => fields.iter().try_fold(0usize, |size, (_, t)| {
+                size.checked_add(self.fixed_size(t, depth + 1)?)
+            }),
+            _ => None,
+        }
+    }
+
+    ///
+    /// decode `data` as the type named `type_name`
+    ///
+    pub fn decode(&self, type_name: &str, data: &[u8]) -> Result<Value, DecodeError> {
+        self.decode_type(type_name, data, 0)
+    }
+
+    fn decode_type(&self, name: &str, data: &[u8], depth: usize) -> Result<Value, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::TooDeep);
+        }
+        let def = self
+            .types
+            .get(name)
+            .ok_or_else(|| DecodeError::UnknownType(name.to_string()))?;
+        let malformed = || DecodeError::Malformed(name.to_string());
+        match def {
+            TypeDef::Byte => match data {
+                [byte] => Ok(Value::Byte(*byte)),
+                _ => Err(malformed()),
+            },
+            TypeDef::Array { item, count } => {
+                if Some(data.len()) != self.fixed_size(name, 0) {
+                    return Err(malformed());
+                }
+                self.decode_items(item, data, *count, depth)
+            }
+            TypeDef::Struct { fields } => {
+                if Some(data.len()) != self.fixed_size(name, 0) {
+                    return Err(malformed());
+                }
+                let mut start = 0;
+                let mut values = Vec::with_capacity(fields.len());
+                for (field, t) in fields {
+                    let end = start + self.fixed_size(t, 0).ok_or_else(malformed)?;
+                    values.push((
+                        field.clone(),
+                        self.decode_type(t, &data[start..end], depth + 1)?,
+                    ));
+                    start = end;
+                }
+                Ok(Value::Fields(values))
+            }
+            TypeDef::Vector { item } => match self.fixed_size(item, 0) {
+                // fixvec
+                Some(item_size) => {
+                    if data.len() < molecule::NUMBER_SIZE {
+                        return Err(malformed());
+                    }
+                    let count = molecule::unpack_number(data) as usize;
+                    let items = &data[molecule::NUMBER_SIZE..];
+                    // the count is limited by the data only if items take space
+                    if item_size == 0 || count.checked_mul(item_size) != Some(items.len()) {
+                        return Err(malformed());
+                    }
+                    self.decode_items(item, items, count, depth)
+                }
+                // dynvec
+                None => {
+                    let ranges = parse_offsets(data).ok_or_else(malformed)?;
+                    let items = ranges
+                        .into_iter()
+                        .map(|range| self.decode_type(item, &data[range], depth + 1))
+                        .collect::<Result<_, _>>()?;
+                    Ok(Value::List(items))
+                }
+            },
+            TypeDef::Option { item } => {
+                if data.is_empty() {
+                    Ok(Value::None)
+                } else {
+                    self.decode_type(item, data, depth + 1)
+                }
+            }
+            TypeDef::Union { items } => {
+                if data.len() < molecule::NUMBER_SIZE {
+                    return Err(malformed());
+                }
+                let item_id = molecule::unpack_number(data);
+                let (item, _) = items.iter().find(|(_, id)| *id == item_id).ok_or(
+                    DecodeError::UnknownUnionItem {
+                        union: name.to_string(),
+                        item_id,
+                    },
+                )?;
+                let value = self.decode_type(item, &data[molecule::NUMBER_SIZE..], depth + 1)?;
+                Ok(Value::Union {
+                    item: item.clone(),
+                    value: Box::new(value),
+                })
+            }
+            TypeDef::Table { fields } => {
+                let ranges = parse_offsets(data).ok_or_else(malformed)?;
+                if ranges.len() != fields.len() {
+                    return Err(malformed());
+                }
+                let values = fields
+                    .iter()
+                    .zip(ranges)
+                    .map(|((field, t), range)| {
+                        Ok((field.clone(), self.decode_type(t, &data[range], depth + 1)?))
+                    })
+                    .collect::<Result<_, DecodeError>>()?;
+                Ok(Value::Fields(values))
+            }
+        }
+    }
+
+    ///
+    /// decode `count` fixed size items, byte items are decoded as Bytes
+    ///
+    fn decode_items(
+        &self,
+        item: &str,
+        data: &[u8],
+        count: usize,
+        depth: usize,
+    ) -> Result<Value, DecodeError> {
+        if self.types.get(item) == Some(&TypeDef::Byte) {
+            return Ok(Value::Bytes(data.to_vec()));
+        }
+        let item_size = self.fixed_size(item, 0).unwrap_or_default();
+        let items = (0..count)
+            .map(|i| self.decode_type(item, &data[i * item_size..(i + 1) * item_size], depth + 1))
+            .collect::<Result<_, _>>()?;
+        Ok(Value::List(items))
+    }
+}
+
+///
+/// the ranges of items in a dynvec or fields in a table, None if the header
+/// is broken
+///
+fn parse_offsets(data: &[u8]) -> Option<Vec<Range<usize>>> {
+    let size = molecule::NUMBER_SIZE;
+    if data.len() < size || molecule::unpack_number(data) as usize != data.len() {
+        return None;
+    }
+    if data.len() == size {
+        return Some(Vec::new());
+    }
+    if data.len() < size * 2 {
+        return None;
+    }
+    let offset_first = molecule::unpack_number(&data[size..]) as usize;
+    if offset_first % size != 0 || offset_first < size * 2 || offset_first > data.len() {
+        return None;
+    }
+    let mut offsets: Vec<usize> = data[size..offset_first]
+        .chunks_exact(size)
+        .map(|x| molecule::unpack_number(x) as usize)
+        .collect();
+    offsets.push(data.len());
+    if offsets.windows(2).any(|i| i[0] > i[1]) {
+        return None;
+    }
+    Some(offsets.windows(2).map(|i| i[0]..i[1]).collect())
+}
+
+///
+/// decode the data of an action with the schema and message_type in its
+/// ScriptInfo
+///
+pub fn decode_action(
+    script_info: ScriptInfoReader,
+    action: ActionReader,
+) -> Result<Value, DecodeError> {
+    let schema = std::str::from_utf8(script_info.schema().raw_data())
+        .map_err(|_| DecodeError::InvalidUtf8)?;
+    let message_type = std::str::from_utf8(script_info.message_type().raw_data())
+        .map_err(|_| DecodeError::InvalidUtf8)?;
+    Schema::parse(schema)?.decode(message_type, action.data().raw_data())
+}
@@ -1,3 +1,9 @@
 pub use ckb_gen_types::packed as blockchain;
+// the modules are generated by moleculec, see `make mol`
+#[allow(clippy::all)]
 pub mod basic;
+// spore.mol imports basic without using it
+#[allow(unused_imports, clippy::all)]
+pub mod spore;
+#[allow(clippy::all)]
 pub mod top_level;
@@ -0,0 +1,155 @@
+//! The syscalls this library takes, behind the `Syscalls` trait.
+//!
+//! Scripts use `VmSyscalls`, the syscalls of ckb-vm through ckb-std. With the
+//! `native` feature the library runs on the host instead: every syscall goes
+//! to the backend installed by `with_syscalls`, e.g. a `NativeTransaction`
+//! fed with a transaction and its resolved input cells. Then `parse_message`,
+//! `fetch_message`, `OtxMessageIter` etc. can be tested with `cargo test`
+//! without building RISC-V binaries.
+
+use alloc::vec::Vec;
+use ckb_std::{ckb_constants::Source, error::SysError, high_level, syscalls};
+use molecule::prelude::Entity;
+
+/// The syscalls used by this library. The partial loading ones follow the
+/// convention of ckb-std: `Ok(len)` if the data from `offset` fits in `buf`,
+/// otherwise `Err(SysError::LengthNotEnough(len))` with `buf` fully filled,
+/// `len` is the length of the data from `offset`.
+pub trait Syscalls {
+    fn load_tx_hash(&self) -> Result<[u8; 32], SysError>;
+    fn load_script_hash(&self) -> Result<[u8; 32], SysError>;
+    /// partially load the molecule serialized transaction
+    fn load_transaction(&self, buf: &mut [u8], offset: usize) -> Result<usize, SysError>;
+    fn load_witness(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError>;
+    /// the molecule serialized `CellOutput`
+    fn load_cell(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError>;
+    /// the molecule serialized `CellInput`
+    fn load_input(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError>;
+    fn load_cell_lock_hash(&self, index: usize, source: Source) -> Result<[u8; 32], SysError>;
+    /// partially load the cell data
+    fn load_cell_data(
+        &self,
+        buf: &mut [u8],
+        offset: usize,
+        index: usize,
+        source: Source,
+    ) -> Result<usize, SysError>;
+}
+
+/// The syscalls of ckb-vm
+pub struct VmSyscalls;
+
+impl Syscalls for VmSyscalls {
+    fn load_tx_hash(&self) -> Result<[u8; 32], SysError> {
+        high_level::load_tx_hash()
+    }
+
+    fn load_script_hash(&self) -> Result<[u8; 32], SysError> {
+        high_level::load_script_hash()
+    }
+
+    fn load_transaction(&self, buf: &mut [u8], offset: usize) -> Result<usize, SysError> {
+        syscalls::load_transaction(buf, offset)
+    }
+
+    fn load_witness(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+        high_level::load_witness(index, source)
+    }
+
+    fn load_cell(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+        high_level::load_cell(index, source).map(|cell| cell.as_slice().to_vec())
+    }
+
+    fn load_input(&self, index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+        high_level::load_input(index, source).map(|input| input.as_slice().to_vec())
+    }
+
+    fn load_cell_lock_hash(&self, index: usize, source: Source) -> Result<[u8; 32], SysError> {
+        high_level::load_cell_lock_hash(index, source)
+    }
+
+    fn load_cell_data(
+        &self,
+        buf: &mut [u8],
+        offset: usize,
+        index: usize,
+        source: Source,
+    ) -> Result<usize, SysError> {
+        syscalls::load_cell_data(buf, offset, index, source)
+    }
+}
+
+#[cfg(not(feature = "native"))]
+fn with<R, F: FnOnce(&dyn Syscalls) -> R>(f: F) -> R {
+    f(&VmSyscalls)
+}
+
+#[cfg(feature = "native")]
+std::thread_local! {
+    static NATIVE: core::cell::RefCell<Option<std::rc::Rc<dyn Syscalls>>> =
+        core::cell::RefCell::new(None);
+}
+
+#[cfg(feature = "native")]
+fn with<R, F: FnOnce(&dyn Syscalls) -> R>(f: F) -> R {
+    let syscalls = NATIVE
+        .with(|native| native.borrow().clone())
+        .expect("no syscalls backend, run the library inside `with_syscalls`");
+    f(syscalls.as_ref())
+}
+
+///
+/// run `f` with all syscalls of the library on the current thread going to
+/// `syscalls`, the previous backend is restored afterwards
+///
+#[cfg(feature = "native")]
+pub fn with_syscalls<S: Syscalls + 'static, R, F: FnOnce() -> R>(syscalls: S, f: F) -> R {
+    struct Restore(Option<std::rc::Rc<dyn Syscalls>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            NATIVE.with(|native| *native.borrow_mut() = previous);
+        }
+    }
+
+    let previous = NATIVE.with(|native| native.borrow_mut().replace(std::rc::Rc::new(syscalls)));
+    let _restore = Restore(previous);
+    f()
+}
+
+pub(crate) fn load_tx_hash() -> Result<[u8; 32], SysError> {
+    with(|s| s.load_tx_hash())
+}
+
+pub(crate) fn load_script_hash() -> Result<[u8; 32], SysError> {
+    with(|s| s.load_script_hash())
+}
+
+pub(crate) fn load_transaction(buf: &mut [u8], offset: usize) -> Result<usize, SysError> {
+    with(|s| s.load_transaction(buf, offset))
+}
+
+pub(crate) fn load_witness(index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+    with(|s| s.load_witness(index, source))
+}
+
+pub(crate) fn load_cell(index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+    with(|s| s.load_cell(index, source))
+}
+
+pub(crate) fn load_input(index: usize, source: Source) -> Result<Vec<u8>, SysError> {
+    with(|s| s.load_input(index, source))
+}
+
+pub(crate) fn load_cell_lock_hash(index: usize, source: Source) -> Result<[u8; 32], SysError> {
+    with(|s| s.load_cell_lock_hash(index, source))
+}
+
+pub(crate) fn load_cell_data(
+    buf: &mut [u8],
+    offset: usize,
+    index: usize,
+    source: Source,
+) -> Result<usize, SysError> {
+    with(|s| s.load_cell_data(buf, offset, index, source))
+}
@@ -0,0 +1,343 @@
+use crate::{
+    fetch_message, fetch_otx_batch,
+    host::{ckb_hash, otx_batch, otx_signing_message_hash, signing_message_hash},
+    native::NativeTransaction,
+    parse_message, parse_otx_message,
+    schemas::{
+        basic::{
+            Action, ActionVec, Message, Otx, OtxStart, ResolvedInputs, SealPair, SealPairVec,
+            SighashAll, SighashAllOnly,
+        },
+        blockchain::{
+            Bytes, BytesVec, CellInput, CellInputVec, CellOutput, CellOutputVec, OutPoint,
+            RawTransaction, Script, ScriptOpt, Transaction,
+        },
+        top_level::{WitnessLayout, WitnessLayoutUnion},
+    },
+    syscalls::{load_script_hash, load_witness},
+    tx_offsets::TxOffsets,
+    Error,
+};
+use ckb_gen_types::prelude::{Builder, Entity, Pack};
+use ckb_std::{ckb_constants::Source, error::SysError};
+
+fn script(id: u8) -> Script {
+    Script::new_builder().args([id].as_slice().pack()).build()
+}
+
+fn script_hash(id: u8) -> [u8; 32] {
+    ckb_hash(script(id).as_slice())
+}
+
+fn message(data: &[u8]) -> Message {
+    let action = Action::new_builder().data(data.pack()).build();
+    Message::new_builder()
+        .actions(ActionVec::new_builder().push(action).build())
+        .build()
+}
+
+fn layout(layout: WitnessLayoutUnion) -> Vec<u8> {
+    WitnessLayout::new_builder()
+        .set(layout)
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+fn sighash_all(message: Message, seal: u8) -> Vec<u8> {
+    layout(WitnessLayoutUnion::SighashAll(
+        SighashAll::new_builder()
+            .message(message)
+            .seal([seal; 65].as_slice().pack())
+            .build(),
+    ))
+}
+
+fn sighash_all_only(seal: u8) -> Vec<u8> {
+    layout(WitnessLayoutUnion::SighashAllOnly(
+        SighashAllOnly::new_builder()
+            .seal([seal; 65].as_slice().pack())
+            .build(),
+    ))
+}
+
+fn otx(inputs: u32, outputs: u32, message: Message, seal_locks: &[u8]) -> Vec<u8> {
+    let seals = seal_locks.iter().map(|lock| {
+        SealPair::new_builder()
+            .script_hash(script_hash(*lock).pack())
+            .seal([*lock; 65].as_slice().pack())
+            .build()
+    });
+    layout(WitnessLayoutUnion::Otx(
+        Otx::new_builder()
+            .input_cells(inputs.pack())
+            .output_cells(outputs.pack())
+            .message(message)
+            .seals(SealPairVec::new_builder().extend(seals).build())
+            .build(),
+    ))
+}
+
+// input cells locked by `locks`, outputs locked by 0 and typed by `types`
+fn build_tx(
+    locks: &[u8],
+    types: &[Option<u8>],
+    witnesses: &[Vec<u8>],
+) -> (Transaction, ResolvedInputs) {
+    let inputs = (0..locks.len()).map(|i| {
+        CellInput::new_builder()
+            .previous_output(OutPoint::new_builder().index((i as u32).pack()).build())
+            .build()
+    });
+    let cells = locks.iter().map(|lock| {
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(script(*lock))
+            .build()
+    });
+    let cells_data = (0..locks.len()).map(|i| -> Bytes { [i as u8].as_slice().pack() });
+    let outputs = types.iter().map(|type_| {
+        CellOutput::new_builder()
+            .capacity(500u64.pack())
+            .lock(script(0))
+            .type_(ScriptOpt::new_builder().set(type_.map(script)).build())
+            .build()
+    });
+    let outputs_data = (0..types.len()).map(|i| -> Bytes { [i as u8; 2].as_slice().pack() });
+    let raw = RawTransaction::new_builder()
+        .inputs(CellInputVec::new_builder().extend(inputs).build())
+        .outputs(CellOutputVec::new_builder().extend(outputs).build())
+        .outputs_data(BytesVec::new_builder().extend(outputs_data).build())
+        .build();
+    let witnesses = witnesses.iter().map(|w| -> Bytes { w.as_slice().pack() });
+    let tx = Transaction::new_builder()
+        .raw(raw)
+        .witnesses(BytesVec::new_builder().extend(witnesses).build())
+        .build();
+    let resolved_inputs = ResolvedInputs::new_builder()
+        .outputs(CellOutputVec::new_builder().extend(cells).build())
+        .outputs_data(BytesVec::new_builder().extend(cells_data).build())
+        .build();
+    (tx, resolved_inputs)
+}
+
+#[test]
+fn test_parse_message_sighash_all() {
+    let m = message(b"MINT");
+    let witnesses = [sighash_all(m.clone(), 1), sighash_all_only(2), Vec::new()];
+    let (tx, resolved_inputs) = build_tx(&[0, 1, 0], &[], &witnesses);
+    let native = NativeTransaction::new(tx.clone(), resolved_inputs.clone());
+    // both groups sign the message of the only SighashAll
+    let expected = signing_message_hash(&Some(m), &tx, &resolved_inputs).unwrap();
+
+    let parsed = native.clone().lock_group(script_hash(0)).run(parse_message);
+    assert_eq!(parsed, Ok((expected, vec![1; 65])));
+    let parsed = native.lock_group(script_hash(1)).run(parse_message);
+    assert_eq!(parsed, Ok((expected, vec![2; 65])));
+}
+
+#[test]
+fn test_parse_message_sighash_all_only() {
+    let witnesses = [sighash_all_only(1), sighash_all_only(2)];
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[], &witnesses);
+    let native = NativeTransaction::new(tx.clone(), resolved_inputs.clone());
+    let expected = signing_message_hash(&None, &tx, &resolved_inputs).unwrap();
+
+    let parsed = native.clone().run(parse_message);
+    assert_eq!(parsed, Ok((expected, vec![1; 65])));
+    let parsed = native.lock_group(script_hash(1)).run(parse_message);
+    assert_eq!(parsed, Ok((expected, vec![2; 65])));
+}
+
+#[test]
+fn test_parse_message_others_in_group() {
+    // the witnesses of the group other than the first one must be empty
+    let witnesses = [sighash_all_only(1), Vec::new(), vec![0]];
+    let (tx, resolved_inputs) = build_tx(&[0, 1, 0], &[], &witnesses);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+    assert_eq!(native.run(parse_message), Err(Error::WrongWitnessLayout));
+}
+
+#[test]
+fn test_fetch_message() {
+    let m = message(b"MINT");
+
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[], &[sighash_all_only(1)]);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+    assert_eq!(native.run(fetch_message).map(|m| m.is_none()), Ok(true));
+
+    let witnesses = [sighash_all_only(1), sighash_all(m.clone(), 2)];
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[], &witnesses);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+    let fetched = native.run(fetch_message).unwrap().unwrap();
+    assert_eq!(fetched.as_slice(), m.as_slice());
+
+    // only one SighashAll in a transaction
+    let witnesses = [sighash_all(m.clone(), 1), sighash_all(m, 2)];
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[], &witnesses);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+    assert_eq!(
+        native.run(fetch_message).map(|m| m.is_some()),
+        Err(Error::WrongWitnessLayout)
+    );
+}
+
+#[test]
+fn test_otx_message_iter() {
+    let (m1, m2) = (message(b"otx 0"), message(b"otx 1"));
+    // otx 0 covers input 0 and output 0, otx 1 covers inputs 1, 2 and output 1
+    let witnesses = [
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        layout(WitnessLayoutUnion::OtxStart(OtxStart::default())),
+        otx(1, 1, m1.clone(), &[0]),
+        otx(2, 1, m2.clone(), &[1, 2]),
+    ];
+    let (tx, resolved_inputs) = build_tx(&[0, 1, 2], &[None, None], &witnesses);
+    let native = NativeTransaction::new(tx.clone(), resolved_inputs.clone());
+    let ranges = otx_batch(&tx).unwrap();
+    assert_eq!(ranges.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [4, 5]);
+
+    let batch = native.clone().run(fetch_otx_batch).unwrap();
+    assert_eq!((batch.start_witness, batch.otx_count), (3, 2));
+    assert_eq!(batch.ranges.input_cells, 0..3);
+    assert_eq!(batch.ranges.output_cells, 0..2);
+
+    // every group only sees the otxs with its input cells
+    let expected = [
+        (
+            0,
+            otx_signing_message_hash(&m1, &tx, &resolved_inputs, &ranges[0].1),
+            1,
+        ),
+        (
+            1,
+            otx_signing_message_hash(&m2, &tx, &resolved_inputs, &ranges[1].1),
+            2,
+        ),
+        (
+            2,
+            otx_signing_message_hash(&m2, &tx, &resolved_inputs, &ranges[1].1),
+            2,
+        ),
+    ];
+    for (lock, digest, seals) in expected {
+        let items: Vec<_> = native.clone().lock_group(script_hash(lock)).run(|| {
+            parse_otx_message(script_hash(lock))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        });
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].0, digest.unwrap());
+        assert_eq!(items[0].1.len(), seals);
+    }
+    let items = native.run(|| parse_otx_message(script_hash(3)).unwrap().count());
+    assert_eq!(items, 0);
+}
+
+#[test]
+fn test_otx_message_iter_out_of_bound() {
+    // otx 1 claims an output cell which doesn't exist
+    let witnesses = [
+        Vec::new(),
+        Vec::new(),
+        layout(WitnessLayoutUnion::OtxStart(OtxStart::default())),
+        otx(1, 1, message(b"otx 0"), &[0]),
+        otx(1, 1, message(b"otx 1"), &[1]),
+    ];
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[None], &witnesses);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+    let items: Vec<_> = native.run(|| parse_otx_message(script_hash(1)).unwrap().collect());
+    assert_eq!(items.len(), 1);
+    assert!(matches!(items[0], Err(Error::OtxOutOfBound(1))));
+}
+
+#[test]
+fn test_lock_group() {
+    let witnesses = [vec![0], vec![1], vec![2]];
+    let (tx, resolved_inputs) = build_tx(&[0, 1, 1], &[Some(5)], &witnesses);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+    // the lock of the first input cell by default
+    assert_eq!(native.clone().run(load_script_hash), Ok(script_hash(0)));
+
+    native.lock_group(script_hash(1)).run(|| {
+        assert_eq!(load_script_hash(), Ok(script_hash(1)));
+        assert_eq!(load_witness(0, Source::GroupInput), Ok(vec![1]));
+        assert_eq!(load_witness(1, Source::GroupInput), Ok(vec![2]));
+        assert_eq!(
+            load_witness(2, Source::GroupInput),
+            Err(SysError::IndexOutOfBound)
+        );
+        // a lock script group has no output cells
+        assert_eq!(
+            load_witness(0, Source::GroupOutput),
+            Err(SysError::IndexOutOfBound)
+        );
+        assert_eq!(load_witness(0, Source::Input), Ok(vec![0]));
+    });
+}
+
+#[test]
+fn test_type_group() {
+    let witnesses = [vec![0], vec![1], vec![2]];
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[None, Some(5), Some(5)], &witnesses);
+    let native = NativeTransaction::new(tx, resolved_inputs);
+
+    native.type_group(script_hash(5)).run(|| {
+        assert_eq!(load_script_hash(), Ok(script_hash(5)));
+        // no input cell has the type script
+        assert_eq!(
+            load_witness(0, Source::GroupInput),
+            Err(SysError::IndexOutOfBound)
+        );
+        assert_eq!(load_witness(0, Source::GroupOutput), Ok(vec![1]));
+        assert_eq!(load_witness(1, Source::GroupOutput), Ok(vec![2]));
+        assert_eq!(
+            load_witness(2, Source::GroupOutput),
+            Err(SysError::IndexOutOfBound)
+        );
+    });
+}
+
+#[test]
+fn test_tx_offsets() {
+    let (tx, resolved_inputs) = build_tx(&[0, 1], &[None, Some(5), None], &[]);
+    let lens = |tx: Vec<u8>| {
+        let tx = Transaction::new_unchecked(tx.into());
+        NativeTransaction::new(tx, resolved_inputs.clone()).run(|| {
+            TxOffsets::load().map(|offsets| {
+                (
+                    offsets.cell_deps_len(),
+                    offsets.header_deps_len(),
+                    offsets.inputs_len(),
+                    offsets.outputs_len(),
+                )
+            })
+        })
+    };
+    assert_eq!(lens(tx.as_slice().to_vec()), Ok((0, 0, 2, 3)));
+
+    // the fields of raw transaction: version, cell_deps, header_deps, inputs,
+    // outputs and outputs_data, their offsets start at 16
+    let offset = |tx: &[u8], field: usize| {
+        let start = 16 + field * 4;
+        u32::from_le_bytes(tx[start..start + 4].try_into().unwrap())
+    };
+    let set_offset = |field: usize, value: u32| {
+        let mut tx = tx.as_slice().to_vec();
+        let start = 16 + field * 4;
+        tx[start..start + 4].copy_from_slice(&value.to_le_bytes());
+        tx
+    };
+    // header_deps before cell_deps
+    let bytes = set_offset(2, offset(tx.as_slice(), 1) - 4);
+    assert_eq!(lens(bytes), Err(Error::MoleculeEncoding));
+    // inputs doesn't fit CellInput
+    let bytes = set_offset(4, offset(tx.as_slice(), 4) - 1);
+    assert_eq!(lens(bytes), Err(Error::MoleculeEncoding));
+    // outputs after outputs_data
+    let bytes = set_offset(4, offset(tx.as_slice(), 5) + 4);
+    assert_eq!(lens(bytes), Err(Error::MoleculeEncoding));
+}
@@ -0,0 +1,163 @@
+use crate::{syscalls::load_transaction, Error};
+use ckb_std::{
+    ckb_types::packed::{Byte32, CellDep, CellInput},
+    error::SysError,
+};
+use molecule::NUMBER_SIZE;
+
+///
+/// the molecule data structure of transaction is:
+/// full-size|raw-offset|witnesses-offset|raw-full-size|version-offset|cell_deps-offset|header_deps-offset|inputs-offset|outputs-offset|outputs_data-offset|...
+/// full-size and offset are 4 bytes. `TxOffsets` only loads these headers and
+/// the header of outputs, the items of cell_deps, header_deps and inputs can
+/// then be counted and loaded one by one by partial loading, without loading
+/// the whole transaction into memory.
+///
+/// All offsets are relative to the beginning of the transaction. They are
+/// validated in `load`, a transaction with offsets going backwards or fixvecs
+/// not fitting their items is rejected with `Error::MoleculeEncoding`.
+///
+pub(crate) struct TxOffsets {
+    cell_deps: usize,
+    header_deps: usize,
+    cell_deps_len: usize,
+    header_deps_len: usize,
+    inputs_len: usize,
+    outputs_len: usize,
+}
+
+///
+/// load the transaction starting at `offset` into `buf`, returns the length of loaded data
+///
+fn load_transaction_at(buf: &mut [u8], offset: usize) -> Result<usize, Error> {
+    match load_transaction(buf, offset) {
+        Ok(len) => Ok(len),
+        // the remaining data is longer than the buffer, the buffer is fully filled
+        Err(SysError::LengthNotEnough(_)) => Ok(buf.len()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read_number(buf: &[u8], index: usize) -> usize {
+    let start = index * NUMBER_SIZE;
+    u32::from_le_bytes(buf[start..start + NUMBER_SIZE].try_into().unwrap()) as usize
+}
+
+///
+/// the item count of a fixvec from `start` to `end`: item-count|item|item|...
+///
+fn fixvec_len(start: usize, end: usize, item_size: usize) -> Result<usize, Error> {
+    let size = end
+        .checked_sub(start)
+        .and_then(|size| size.checked_sub(NUMBER_SIZE))
+        .ok_or(Error::MoleculeEncoding)?;
+    if size % item_size != 0 {
+        return Err(Error::MoleculeEncoding);
+    }
+    Ok(size / item_size)
+}
+
+impl TxOffsets {
+    pub fn load() -> Result<Self, Error> {
+        // full-size|raw-offset
+        let mut tx_header = [0u8; NUMBER_SIZE * 2];
+        if load_transaction_at(&mut tx_header, 0)? != tx_header.len() {
+            return Err(Error::MoleculeEncoding);
+        }
+        let raw = read_number(&tx_header, 1);
+
+        // full-size|version-offset|cell_deps-offset|header_deps-offset|inputs-offset|outputs-offset|outputs_data-offset
+        let mut raw_header = [0u8; NUMBER_SIZE * 7];
+        if load_transaction_at(&mut raw_header, raw)? != raw_header.len() {
+            return Err(Error::MoleculeEncoding);
+        }
+        // version is a Uint32 between the header and cell_deps
+        let version = raw + read_number(&raw_header, 1);
+        let cell_deps = raw + read_number(&raw_header, 2);
+        let header_deps = raw + read_number(&raw_header, 3);
+        let inputs = raw + read_number(&raw_header, 4);
+        let outputs = raw + read_number(&raw_header, 5);
+        let outputs_data = raw + read_number(&raw_header, 6);
+        if version < raw + raw_header.len()
+            || cell_deps != version + NUMBER_SIZE
+            || outputs > outputs_data
+        {
+            return Err(Error::MoleculeEncoding);
+        }
+        let cell_deps_len = fixvec_len(cell_deps, header_deps, CellDep::TOTAL_SIZE)?;
+        let header_deps_len = fixvec_len(header_deps, inputs, Byte32::TOTAL_SIZE)?;
+        let inputs_len = fixvec_len(inputs, outputs, CellInput::TOTAL_SIZE)?;
+
+        // outputs is a dynvec: full-size|first-offset|..., it's always followed
+        // by outputs_data so the header can be fully loaded even it's empty
+        let mut outputs_header = [0u8; NUMBER_SIZE * 2];
+        if load_transaction_at(&mut outputs_header, outputs)? != outputs_header.len() {
+            return Err(Error::MoleculeEncoding);
+        }
+        let outputs_len = if read_number(&outputs_header, 0) == NUMBER_SIZE {
+            0
+        } else {
+            // the first offset is right after the offsets of all items
+            let first = read_number(&outputs_header, 1);
+            if first < NUMBER_SIZE * 2 || first % NUMBER_SIZE != 0 {
+                return Err(Error::MoleculeEncoding);
+            }
+            first / NUMBER_SIZE - 1
+        };
+
+        Ok(Self {
+            cell_deps,
+            header_deps,
+            cell_deps_len,
+            header_deps_len,
+            inputs_len,
+            outputs_len,
+        })
+    }
+
+    pub fn cell_deps_len(&self) -> usize {
+        self.cell_deps_len
+    }
+
+    pub fn header_deps_len(&self) -> usize {
+        self.header_deps_len
+    }
+
+    pub fn inputs_len(&self) -> usize {
+        self.inputs_len
+    }
+
+    pub fn outputs_len(&self) -> usize {
+        self.outputs_len
+    }
+
+    ///
+    /// load the molecule serialized `CellDep` at `index`
+    ///
+    pub fn load_cell_dep(&self, index: usize) -> Result<[u8; CellDep::TOTAL_SIZE], Error> {
+        if index >= self.cell_deps_len() {
+            return Err(SysError::IndexOutOfBound.into());
+        }
+        let mut cell_dep = [0u8; CellDep::TOTAL_SIZE];
+        let offset = self.cell_deps + NUMBER_SIZE + index * CellDep::TOTAL_SIZE;
+        if load_transaction_at(&mut cell_dep, offset)? != cell_dep.len() {
+            return Err(Error::MoleculeEncoding);
+        }
+        Ok(cell_dep)
+    }
+
+    ///
+    /// load the header dep (block hash) at `index`
+    ///
+    pub fn load_header_dep(&self, index: usize) -> Result<[u8; Byte32::TOTAL_SIZE], Error> {
+        if index >= self.header_deps_len() {
+            return Err(SysError::IndexOutOfBound.into());
+        }
+        let mut header_dep = [0u8; Byte32::TOTAL_SIZE];
+        let offset = self.header_deps + NUMBER_SIZE + index * Byte32::TOTAL_SIZE;
+        if load_transaction_at(&mut header_dep, offset)? != header_dep.len() {
+            return Err(Error::MoleculeEncoding);
+        }
+        Ok(header_dep)
+    }
+}
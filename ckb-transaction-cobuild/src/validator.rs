@@ -0,0 +1,206 @@
+use crate::{
+    classify::{classify_witness, WitnessKind},
+    schemas::basic::{OtxReader, OtxStartReader},
+    syscalls::load_witness,
+    tx_offsets::TxOffsets,
+    Error,
+};
+use alloc::vec::Vec;
+use ckb_gen_types::prelude::Unpack;
+use ckb_std::{ckb_constants::Source, high_level::QueryIter};
+use core::ops::Range;
+use molecule::{prelude::Reader, NUMBER_SIZE};
+
+/// the item ids of WitnessLayout union, from SighashAll to OtxStart
+const WITNESS_LAYOUT_IDS: Range<u32> = 4278190081..4278190085;
+
+/// A violation of the witness layout rules, carrying the index of the witness
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// the witness starts with an item id of WitnessLayout but fails molecule verification
+    MalformedWitnessLayout(usize),
+    /// SighashAll appears more than once, both indexes are given
+    MultipleSighashAll { first: usize, second: usize },
+    /// SighashAll or SighashAllOnly beyond the input cells, it seals nothing
+    SighashBeyondInputs(usize),
+    /// SighashAll or SighashAllOnly at the index of an input cell covered by the otx batch
+    SighashAtOtxInput(usize),
+    /// OtxStart appears more than once
+    MultipleOtxStart(usize),
+    /// OtxStart isn't followed by any Otx
+    OtxStartWithoutOtx(usize),
+    /// Otx without a preceding OtxStart
+    OtxWithoutOtxStart(usize),
+    /// Otx not immediately following OtxStart or another Otx
+    OtxNotContiguous(usize),
+}
+
+/// The result of validating all witnesses of a transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// index of the SighashAll witness, the first one if there are more
+    pub sighash_all: Option<usize>,
+    /// indexes of SighashAllOnly witnesses
+    pub sighash_all_only: Vec<usize>,
+    /// index of the OtxStart witness, the first one if there are more
+    pub otx_start: Option<usize>,
+    /// indexes of the Otx witnesses following OtxStart
+    pub otx: Range<usize>,
+    /// input cells covered by the otx batch, saturated at usize::MAX
+    pub otx_input_cells: Range<usize>,
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    ///
+    /// returns Error::WrongWitnessLayout if there is any violation
+    ///
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(Error::WrongWitnessLayout)
+        }
+    }
+}
+
+/// WitnessValidator checks the witnesses pushed one by one, so that on-chain
+/// scripts never need to hold all witnesses in memory.
+pub struct WitnessValidator {
+    inputs_len: usize,
+    index: usize,
+    report: ValidationReport,
+}
+
+impl WitnessValidator {
+    pub fn new(inputs_len: usize) -> Self {
+        Self {
+            inputs_len,
+            index: 0,
+            report: Default::default(),
+        }
+    }
+
+    ///
+    /// validate the next witness
+    ///
+    pub fn push(&mut self, witness: &[u8]) {
+        let index = self.index;
+        self.index += 1;
+        let report = &mut self.report;
+
+        match classify_witness(witness) {
+            WitnessKind::SighashAll { .. } => {
+                match report.sighash_all {
+                    Some(first) => report.violations.push(Violation::MultipleSighashAll {
+                        first,
+                        second: index,
+                    }),
+                    None => report.sighash_all = Some(index),
+                }
+                if index >= self.inputs_len {
+                    report
+                        .violations
+                        .push(Violation::SighashBeyondInputs(index));
+                }
+            }
+            WitnessKind::SighashAllOnly { .. } => {
+                report.sighash_all_only.push(index);
+                if index >= self.inputs_len {
+                    report
+                        .violations
+                        .push(Violation::SighashBeyondInputs(index));
+                }
+            }
+            WitnessKind::OtxStart { otx_start } => {
+                if report.otx_start.is_some() {
+                    report.violations.push(Violation::MultipleOtxStart(index));
+                } else {
+                    let otx_start = OtxStartReader::new_unchecked(&witness[otx_start]);
+                    let start: u32 = otx_start.start_input_cell().unpack();
+                    report.otx_start = Some(index);
+                    report.otx = index + 1..index + 1;
+                    report.otx_input_cells = start as usize..start as usize;
+                }
+            }
+            WitnessKind::Otx { otx, .. } => {
+                if report.otx_start.is_none() {
+                    report.violations.push(Violation::OtxWithoutOtxStart(index));
+                } else if report.otx.end != index {
+                    report.violations.push(Violation::OtxNotContiguous(index));
+                } else {
+                    let otx = OtxReader::new_unchecked(&witness[otx]);
+                    let input_cells: u32 = otx.input_cells().unpack();
+                    report.otx.end += 1;
+                    report.otx_input_cells.end = report
+                        .otx_input_cells
+                        .end
+                        .saturating_add(input_cells as usize);
+                }
+            }
+            WitnessKind::Unknown => {
+                if witness.len() >= NUMBER_SIZE
+                    && WITNESS_LAYOUT_IDS.contains(&molecule::unpack_number(witness))
+                {
+                    report
+                        .violations
+                        .push(Violation::MalformedWitnessLayout(index));
+                }
+            }
+            WitnessKind::WitnessArgs { .. } | WitnessKind::Empty => {}
+        }
+    }
+
+    ///
+    /// finish the validation after all witnesses are pushed
+    ///
+    pub fn finish(mut self) -> ValidationReport {
+        let report = &mut self.report;
+        if let Some(otx_start) = report.otx_start {
+            if report.otx.is_empty() {
+                report
+                    .violations
+                    .push(Violation::OtxStartWithoutOtx(otx_start));
+            }
+        }
+        let sighash = report.sighash_all.iter().chain(&report.sighash_all_only);
+        let mut at_otx_input: Vec<usize> = sighash
+            .filter(|index| report.otx_input_cells.contains(*index))
+            .copied()
+            .collect();
+        at_otx_input.sort_unstable();
+        report
+            .violations
+            .extend(at_otx_input.into_iter().map(Violation::SighashAtOtxInput));
+        self.report
+    }
+}
+
+///
+/// validate the layout of all witnesses in a transaction with `inputs_len`
+/// input cells. This function doesn't depend on syscalls, it can be used by
+/// off-chain tools.
+///
+pub fn validate_witnesses<W: AsRef<[u8]>>(witnesses: &[W], inputs_len: usize) -> ValidationReport {
+    let mut validator = WitnessValidator::new(inputs_len);
+    for witness in witnesses {
+        validator.push(witness.as_ref());
+    }
+    validator.finish()
+}
+
+///
+/// validate the layout of all witnesses in current transaction
+/// This function is mainly used by lock script
+///
+pub fn validate_current_transaction() -> Result<ValidationReport, Error> {
+    let mut validator = WitnessValidator::new(TxOffsets::load()?.inputs_len());
+    for witness in QueryIter::new(load_witness, Source::Input) {
+        validator.push(&witness);
+    }
+    Ok(validator.finish())
+}
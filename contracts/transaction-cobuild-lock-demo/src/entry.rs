@@ -2,7 +2,7 @@ use ckb_std::{
     ckb_types::{bytes::Bytes, core::ScriptHashType, prelude::*},
     high_level::load_script,
 };
-use ckb_transaction_cobuild::parse_message;
+use ckb_transaction_cobuild::{cobuild_entry, parse_legacy_message};
 use core::result::Result;
 
 use crate::error::Error;
@@ -17,30 +17,36 @@ const AUTH_CODE_HASH: [u8; 32] = [
 ];
 
 pub fn main() -> Result<(), Error> {
-    if let Ok((message_digest, seal)) = parse_message() {
-        let mut pubkey_hash = [0u8; 20];
-        let script = load_script()?;
-        let args: Bytes = script.args().unpack();
-        pubkey_hash.copy_from_slice(&args[0..20]);
+    let mut pubkey_hash = [0u8; 20];
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    pubkey_hash.copy_from_slice(&args[0..20]);
 
-        let id = CkbAuthType {
-            algorithm_id: AuthAlgorithmIdType::Ckb,
-            pubkey_hash,
-        };
+    let id = CkbAuthType {
+        algorithm_id: AuthAlgorithmIdType::Ckb,
+        pubkey_hash,
+    };
 
-        let entry = CkbEntryType {
-            code_hash: AUTH_CODE_HASH,
-            hash_type: ScriptHashType::Data1,
-            entry_category: EntryCategoryType::DynamicLinking,
-        };
-
-        ckb_auth(&entry, &id, &seal, &message_digest).map_err(|_| Error::AuthError)?;
+    let entry = CkbEntryType {
+        code_hash: AUTH_CODE_HASH,
+        hash_type: ScriptHashType::Data1,
+        entry_category: EntryCategoryType::DynamicLinking,
+    };
 
+    let verify = |seal: &[u8], message_digest: &[u8; 32]| {
+        ckb_auth(&entry, &id, seal, message_digest).is_ok()
+    };
+    if cobuild_entry(&verify)? {
         Ok(())
     } else {
         // In this routine, it indicates that the WitnessLayout is not being
-        // used. It is possible that the traditional WitnessArgs is being used.
-        // The previous code can be copied and pasted here.
-        Ok(())
+        // used. The traditional WitnessArgs is verified in the same way as
+        // secp256k1_blake160_sighash_all.
+        let (message_digest, seal) = parse_legacy_message()?;
+        if verify(&seal, &message_digest) {
+            Ok(())
+        } else {
+            Err(Error::AuthError)
+        }
     }
 }
@@ -13,6 +13,10 @@ pub enum Error {
     WrongSighashAll,
     WrongWitnessLayout,
     WrongOtxStart,
+    OtxStartOutOfBound,
+    OtxOutOfBound,
+    NoMatchedAction,
+    MultipleMatchedActions,
 }
 
 impl From<SysError> for Error {
@@ -36,6 +40,11 @@ impl From<ckb_transaction_cobuild::Error> for Error {
             ckb_transaction_cobuild::Error::WrongSighashAll => Error::WrongSighashAll,
             ckb_transaction_cobuild::Error::WrongWitnessLayout => Error::WrongWitnessLayout,
             ckb_transaction_cobuild::Error::WrongOtxStart => Error::WrongOtxStart,
+            ckb_transaction_cobuild::Error::OtxStartOutOfBound => Error::OtxStartOutOfBound,
+            ckb_transaction_cobuild::Error::OtxOutOfBound(_) => Error::OtxOutOfBound,
+            ckb_transaction_cobuild::Error::AuthError => Error::AuthError,
+            ckb_transaction_cobuild::Error::NoMatchedAction => Error::NoMatchedAction,
+            ckb_transaction_cobuild::Error::MultipleMatchedActions => Error::MultipleMatchedActions,
         }
     }
 }
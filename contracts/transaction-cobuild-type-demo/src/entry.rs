@@ -1,23 +1,35 @@
+use alloc::vec::Vec;
 use ckb_std::{
     ckb_constants::Source,
     ckb_types::prelude::*,
-    high_level::{load_cell_data, load_script_hash},
+    high_level::{load_cell_data, load_cell_type_hash, load_script_hash},
     syscalls::SysError,
 };
-use ckb_transaction_cobuild::fetch_message;
-use core::result::Result;
+use ckb_transaction_cobuild::{fetch_message_witness, fetch_otx_messages, ActionIter};
+use core::{ops::Range, result::Result};
 
 use crate::error::Error;
 
 pub fn main() -> Result<(), Error> {
+    let script_hash = load_script_hash()?;
     // fetch the message field of SighashAll and verify it
-    if let Ok(Some(message)) = fetch_message() {
-        let script_hash = load_script_hash()?;
-        for action in message.actions().into_iter() {
-            if action.script_hash().as_slice() == script_hash.as_slice() {
-                if !verify_action_data(&action.data().raw_data())? {
-                    return Err(Error::InvalidMessage);
-                }
+    if let Ok(Some(witness)) = fetch_message_witness() {
+        for action in witness.actions(script_hash) {
+            let input = load_group_cell_data(Source::GroupInput)?;
+            let output = load_group_cell_data(Source::GroupOutput)?;
+            if !verify_action_data(action.data().raw_data(), input, output) {
+                return Err(Error::InvalidMessage);
+            }
+        }
+    }
+    // the message of every otx is verified against the cells in the otx
+    for item in fetch_otx_messages()? {
+        let (message, ranges) = item?;
+        for action in ActionIter::new(message.as_reader(), script_hash) {
+            let input = find_cell_data(&script_hash, ranges.input_cells.clone(), Source::Input)?;
+            let output = find_cell_data(&script_hash, ranges.output_cells.clone(), Source::Output)?;
+            if !verify_action_data(action.data().raw_data(), input, output) {
+                return Err(Error::InvalidMessage);
             }
         }
     }
@@ -25,16 +37,34 @@ pub fn main() -> Result<(), Error> {
     Ok(())
 }
 
-// a simple verification logic for the demo
-fn verify_action_data(data: &[u8]) -> Result<bool, Error> {
-    if data == b"MINT" {
-        if let Err(SysError::IndexOutOfBound) = load_cell_data(0, Source::GroupInput) {
-            return Ok(!load_cell_data(0, Source::GroupOutput)?.is_empty());
-        }
-    } else if data == b"BURN" {
-        if let Err(SysError::IndexOutOfBound) = load_cell_data(0, Source::GroupOutput) {
-            return Ok(!load_cell_data(0, Source::GroupInput)?.is_empty());
+// the data of first cell in script group, None if there is no cell
+fn load_group_cell_data(source: Source) -> Result<Option<Vec<u8>>, Error> {
+    match load_cell_data(0, source) {
+        Ok(data) => Ok(Some(data)),
+        Err(SysError::IndexOutOfBound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// the data of first cell with current type script in the range, None if there is no cell
+fn find_cell_data(
+    script_hash: &[u8; 32],
+    range: Range<usize>,
+    source: Source,
+) -> Result<Option<Vec<u8>>, Error> {
+    for i in range {
+        if load_cell_type_hash(i, source)?.as_ref() == Some(script_hash) {
+            return Ok(Some(load_cell_data(i, source)?));
         }
     }
-    Ok(false)
+    Ok(None)
+}
+
+// a simple verification logic for the demo
+fn verify_action_data(data: &[u8], input: Option<Vec<u8>>, output: Option<Vec<u8>>) -> bool {
+    match (data, input, output) {
+        (b"MINT", None, Some(output)) => !output.is_empty(),
+        (b"BURN", Some(input), None) => !input.is_empty(),
+        _ => false,
+    }
 }
@@ -1,4 +1,5 @@
 use ckb_std::error::SysError;
+use ckb_transaction_cobuild;
 
 /// Error
 #[repr(i8)]
@@ -23,3 +24,20 @@ impl From<SysError> for Error {
         }
     }
 }
+
+impl From<ckb_transaction_cobuild::Error> for Error {
+    fn from(err: ckb_transaction_cobuild::Error) -> Self {
+        match err {
+            ckb_transaction_cobuild::Error::Sys(e) => e.into(),
+            ckb_transaction_cobuild::Error::MoleculeEncoding => Error::Encoding,
+            ckb_transaction_cobuild::Error::WrongSighashAll
+            | ckb_transaction_cobuild::Error::WrongWitnessLayout
+            | ckb_transaction_cobuild::Error::WrongOtxStart
+            | ckb_transaction_cobuild::Error::OtxStartOutOfBound
+            | ckb_transaction_cobuild::Error::OtxOutOfBound(_)
+            | ckb_transaction_cobuild::Error::AuthError
+            | ckb_transaction_cobuild::Error::NoMatchedAction
+            | ckb_transaction_cobuild::Error::MultipleMatchedActions => Error::InvalidMessage,
+        }
+    }
+}
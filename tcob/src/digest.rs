@@ -0,0 +1,95 @@
+//! Calculate the signing message hashes of a transaction, optionally with
+//! every hashed component, to compare with the `debug!` log of scripts built
+//! with the `debug` feature of ckb-transaction-cobuild.
+
+use crate::tx::LoadedTx;
+use ckb_transaction_cobuild::{
+    classify_witness,
+    digest::DigestTrace,
+    host::{explain_otx_signing_message_hash, explain_signing_message_hash, otx_batch},
+    schemas::{
+        basic::Message,
+        blockchain::Transaction,
+        top_level::{WitnessLayout, WitnessLayoutUnion},
+    },
+    Error, WitnessKind,
+};
+use ckb_types::prelude::Entity;
+use std::fmt::Write;
+
+fn digest_error(e: Error) -> String {
+    format!("failed to calculate the signing message hash: {:?}", e)
+}
+
+fn write_trace(out: &mut String, title: &str, trace: &DigestTrace, explain: bool) {
+    if explain {
+        writeln!(out, "{}:\n{}\n", title, trace).unwrap();
+    } else {
+        writeln!(out, "{}: 0x{}", title, hex::encode(trace.digest)).unwrap();
+    }
+}
+
+///
+/// the message of the only SighashAll witness, the same as `fetch_message`
+///
+pub fn sighash_all_message(tx: &Transaction) -> Result<Option<Message>, String> {
+    let mut message = None;
+    for witness in tx.witnesses().into_iter() {
+        let witness = witness.raw_data();
+        if let WitnessKind::SighashAll { message: range, .. } = classify_witness(&witness) {
+            if message.is_some() {
+                return Err("more than one SighashAll witness".to_string());
+            }
+            message = Some(Message::new_unchecked(witness.slice(range)));
+        }
+    }
+    Ok(message)
+}
+
+///
+/// the message of the Otx witness at `witness_index`, as found by `otx_batch`
+///
+pub fn otx_message(tx: &Transaction, witness_index: usize) -> Message {
+    let witness = tx.witnesses().get(witness_index).unwrap().raw_data();
+    match WitnessLayout::new_unchecked(witness).to_enum() {
+        WitnessLayoutUnion::Otx(otx) => otx.message(),
+        _ => unreachable!(),
+    }
+}
+
+///
+/// the SighashAll (or SighashAllOnly) signing message hash and the one of
+/// every otx, with their components if `explain` is set
+///
+pub fn digest(loaded: &LoadedTx, explain: bool) -> Result<String, String> {
+    let resolved_inputs = loaded
+        .resolved_inputs
+        .as_ref()
+        .ok_or("the input cells are required, use a mock transaction")?;
+    let tx = &loaded.tx;
+    let mut out = String::new();
+
+    let message = sighash_all_message(tx)?;
+    let title = if message.is_some() {
+        "SighashAll"
+    } else {
+        "SighashAllOnly"
+    };
+    let trace =
+        explain_signing_message_hash(&message, tx, resolved_inputs).map_err(digest_error)?;
+    write_trace(&mut out, title, &trace, explain);
+
+    let batch = match otx_batch(tx) {
+        Ok(batch) => batch,
+        Err(Error::WrongOtxStart) => Vec::new(),
+        Err(e) => return Err(format!("invalid otx batch: {:?}", e)),
+    };
+    for (otx_index, (witness_index, ranges)) in batch.iter().enumerate() {
+        let message = otx_message(tx, *witness_index);
+        let trace = explain_otx_signing_message_hash(&message, tx, resolved_inputs, ranges)
+            .map_err(digest_error)?;
+        let title = format!("Otx {} (witness {})", otx_index, witness_index);
+        write_trace(&mut out, &title, &trace, explain);
+    }
+    Ok(out)
+}
@@ -0,0 +1,277 @@
+//! Print the witness layouts, messages, otx ranges and script groups of a
+//! transaction.
+
+use crate::tx::LoadedTx;
+use ckb_transaction_cobuild::{
+    classify_witness,
+    host::ckb_hash,
+    parse_spore_action,
+    schemas::basic::{MessageReader, OtxReader, OtxStartReader, SealPairVecReader},
+    validate_witnesses, WitnessKind,
+};
+use ckb_types::prelude::{Entity, Reader, Unpack};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+fn to_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(data))
+}
+
+fn kind_name(kind: &WitnessKind) -> &'static str {
+    match kind {
+        WitnessKind::SighashAll { .. } => "SighashAll",
+        WitnessKind::SighashAllOnly { .. } => "SighashAllOnly",
+        WitnessKind::OtxStart { .. } => "OtxStart",
+        WitnessKind::Otx { .. } => "Otx",
+        WitnessKind::WitnessArgs { .. } => "WitnessArgs",
+        WitnessKind::Empty => "empty",
+        WitnessKind::Unknown => "unknown",
+    }
+}
+
+fn write_message(out: &mut String, message: MessageReader) {
+    let actions = message.actions();
+    writeln!(out, "  message: {} action(s)", actions.len()).unwrap();
+    for (i, action) in actions.iter().enumerate() {
+        let data = action.data().raw_data();
+        writeln!(out, "    action {}:", i).unwrap();
+        writeln!(
+            out,
+            "      script_info_hash: {}",
+            to_hex(action.script_info_hash().as_slice())
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      script_hash: {}",
+            to_hex(action.script_hash().as_slice())
+        )
+        .unwrap();
+        writeln!(out, "      data: {} ({} bytes)", to_hex(data), data.len()).unwrap();
+        if let Ok(spore_action) = parse_spore_action(action) {
+            writeln!(out, "      as SporeAction: {}", spore_action).unwrap();
+        }
+    }
+}
+
+fn write_seals(out: &mut String, seals: SealPairVecReader) {
+    writeln!(out, "  seals: {}", seals.len()).unwrap();
+    for seal in seals.iter() {
+        writeln!(
+            out,
+            "    script_hash {}: {}",
+            to_hex(seal.script_hash().as_slice()),
+            to_hex(seal.seal().raw_data())
+        )
+        .unwrap();
+    }
+}
+
+fn write_range(out: &mut String, name: &str, range: &Range<usize>) {
+    writeln!(out, "    {}: {}..{}", name, range.start, range.end).unwrap();
+}
+
+///
+/// the description of every witness, the otx batch and the script groups
+///
+pub fn inspect(loaded: &LoadedTx) -> String {
+    let mut out = String::new();
+    let tx = &loaded.tx;
+    let raw = tx.raw();
+    let inputs_len = raw.inputs().len();
+    writeln!(out, "transaction {}", to_hex(&ckb_hash(raw.as_slice()))).unwrap();
+    writeln!(
+        out,
+        "inputs: {}, outputs: {}, cell deps: {}, header deps: {}, witnesses: {}",
+        inputs_len,
+        raw.outputs().len(),
+        raw.cell_deps().len(),
+        raw.header_deps().len(),
+        tx.witnesses().len()
+    )
+    .unwrap();
+
+    // otx batch: the cells covered by every Otx, starting from OtxStart
+    let mut otx_cursor: Option<[usize; 4]> = None;
+    let witnesses: Vec<Vec<u8>> = tx
+        .witnesses()
+        .into_iter()
+        .map(|witness| witness.raw_data().to_vec())
+        .collect();
+    let mut kinds = Vec::with_capacity(witnesses.len());
+    for (index, witness) in witnesses.iter().enumerate() {
+        let kind = classify_witness(witness);
+        writeln!(
+            out,
+            "\nwitness {}: {} ({} bytes)",
+            index,
+            kind_name(&kind),
+            witness.len()
+        )
+        .unwrap();
+        match &kind {
+            WitnessKind::SighashAll { seal, message } => {
+                writeln!(out, "  seal: {}", to_hex(&witness[seal.clone()])).unwrap();
+                write_message(
+                    &mut out,
+                    MessageReader::new_unchecked(&witness[message.clone()]),
+                );
+            }
+            WitnessKind::SighashAllOnly { seal } => {
+                writeln!(out, "  seal: {}", to_hex(&witness[seal.clone()])).unwrap();
+            }
+            WitnessKind::OtxStart { otx_start } => {
+                let otx_start = OtxStartReader::new_unchecked(&witness[otx_start.clone()]);
+                let start: [u32; 4] = [
+                    otx_start.start_input_cell().unpack(),
+                    otx_start.start_output_cell().unpack(),
+                    otx_start.start_cell_deps().unpack(),
+                    otx_start.start_header_deps().unpack(),
+                ];
+                writeln!(
+                    out,
+                    "  start: input cell {}, output cell {}, cell dep {}, header dep {}",
+                    start[0], start[1], start[2], start[3]
+                )
+                .unwrap();
+                otx_cursor = Some(start.map(|n| n as usize));
+            }
+            WitnessKind::Otx {
+                otx,
+                message,
+                seals,
+            } => {
+                let otx = OtxReader::new_unchecked(&witness[otx.clone()]);
+                let counts: [u32; 4] = [
+                    otx.input_cells().unpack(),
+                    otx.output_cells().unpack(),
+                    otx.cell_deps().unpack(),
+                    otx.header_deps().unpack(),
+                ];
+                match otx_cursor.as_mut() {
+                    Some(cursor) => {
+                        writeln!(out, "  covers:").unwrap();
+                        let names = ["input cells", "output cells", "cell deps", "header deps"];
+                        for ((start, count), name) in cursor.iter_mut().zip(counts).zip(names) {
+                            let end = start.saturating_add(count as usize);
+                            write_range(&mut out, name, &(*start..end));
+                            *start = end;
+                        }
+                    }
+                    None => writeln!(out, "  covers: unknown, no OtxStart before it").unwrap(),
+                }
+                write_message(
+                    &mut out,
+                    MessageReader::new_unchecked(&witness[message.clone()]),
+                );
+                write_seals(
+                    &mut out,
+                    SealPairVecReader::new_unchecked(&witness[seals.clone()]),
+                );
+            }
+            WitnessKind::WitnessArgs {
+                lock,
+                input_type,
+                output_type,
+            } => {
+                for (name, field) in [
+                    ("lock", lock),
+                    ("input_type", input_type),
+                    ("output_type", output_type),
+                ] {
+                    match field {
+                        Some(range) => {
+                            writeln!(out, "  {}: {}", name, to_hex(&witness[range.clone()]))
+                                .unwrap()
+                        }
+                        None => writeln!(out, "  {}: none", name).unwrap(),
+                    }
+                }
+            }
+            WitnessKind::Empty => {}
+            WitnessKind::Unknown => writeln!(out, "  raw: {}", to_hex(witness)).unwrap(),
+        }
+        kinds.push(kind);
+    }
+
+    let report = validate_witnesses(&witnesses, inputs_len);
+    if !report.is_valid() {
+        writeln!(out, "\nlayout violations:").unwrap();
+        for violation in &report.violations {
+            writeln!(out, "  {:?}", violation).unwrap();
+        }
+    }
+
+    write_script_groups(&mut out, loaded, &kinds);
+    out
+}
+
+#[derive(Default)]
+struct ScriptGroup {
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+fn write_group(
+    out: &mut String,
+    kind: &str,
+    hash: &[u8; 32],
+    group: &ScriptGroup,
+    kinds: &[WitnessKind],
+) {
+    writeln!(out, "  {} {}", kind, to_hex(hash)).unwrap();
+    if !group.inputs.is_empty() {
+        writeln!(out, "    inputs: {:?}", group.inputs).unwrap();
+    }
+    if !group.outputs.is_empty() {
+        writeln!(out, "    outputs: {:?}", group.outputs).unwrap();
+    }
+    let mut witnesses: Vec<usize> = group.inputs.iter().chain(&group.outputs).copied().collect();
+    witnesses.sort_unstable();
+    witnesses.dedup();
+    let witnesses: Vec<String> = witnesses
+        .into_iter()
+        .map(|i| match kinds.get(i) {
+            Some(kind) => format!("{} ({})", i, kind_name(kind)),
+            None => format!("{} (missing)", i),
+        })
+        .collect();
+    writeln!(out, "    witnesses: {}", witnesses.join(", ")).unwrap();
+}
+
+fn write_script_groups(out: &mut String, loaded: &LoadedTx, kinds: &[WitnessKind]) {
+    let mut lock_groups: BTreeMap<[u8; 32], ScriptGroup> = BTreeMap::new();
+    let mut type_groups: BTreeMap<[u8; 32], ScriptGroup> = BTreeMap::new();
+    if let Some(resolved_inputs) = &loaded.resolved_inputs {
+        for (i, cell) in resolved_inputs.outputs().into_iter().enumerate() {
+            let lock = ckb_hash(cell.lock().as_slice());
+            lock_groups.entry(lock).or_default().inputs.push(i);
+            if let Some(script) = cell.type_().to_opt() {
+                let hash = ckb_hash(script.as_slice());
+                type_groups.entry(hash).or_default().inputs.push(i);
+            }
+        }
+    }
+    for (i, cell) in loaded.tx.raw().outputs().into_iter().enumerate() {
+        if let Some(script) = cell.type_().to_opt() {
+            let hash = ckb_hash(script.as_slice());
+            type_groups.entry(hash).or_default().outputs.push(i);
+        }
+    }
+
+    writeln!(out, "\nscript groups:").unwrap();
+    if loaded.resolved_inputs.is_none() {
+        writeln!(
+            out,
+            "  input cells are unknown, only type scripts of outputs are listed"
+        )
+        .unwrap();
+    }
+    for (hash, group) in &lock_groups {
+        write_group(out, "lock", hash, group, kinds);
+    }
+    for (hash, group) in &type_groups {
+        write_group(out, "type", hash, group, kinds);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod digest;
+pub mod inspect;
+pub mod sign;
+pub mod tx;
+pub mod vectors;
@@ -0,0 +1,111 @@
+use ckb_transaction_cobuild::schemas::blockchain;
+use ckb_types::prelude::Entity;
+use clap::{Parser, Subcommand};
+use tcob::{digest, inspect::inspect, sign, tx, vectors};
+
+/// Tools for CKB transaction co-build protocol
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the witness layouts, messages, otx ranges and script groups of a
+    /// transaction in molecule hex, CKB JSON or mock transaction JSON
+    Inspect {
+        /// The transaction file, "-" for stdin
+        tx: String,
+    },
+    /// Print the signing message hashes of a mock transaction: SighashAll (or
+    /// SighashAllOnly) and every otx
+    Digest {
+        /// The mock transaction file, "-" for stdin
+        tx: String,
+        /// Print every hashed component with its offset, length and hash, the
+        /// same as scripts built with the `debug` feature log
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Sign a BuildingPacket (JSON or molecule hex) with a secp256k1 key and
+    /// write the finished transaction
+    Sign {
+        /// The BuildingPacket file, "-" for stdin
+        #[arg(long)]
+        packet: String,
+        /// The private key file, hex on the first line
+        #[arg(long)]
+        key: String,
+        /// Write the transaction to this file instead of stdout
+        #[arg(long)]
+        output: Option<String>,
+        /// Write the transaction in molecule hex instead of CKB JSON
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Print the test vectors of signing message hashes in JSON, or check a
+    /// corpus of them against this implementation
+    Vectors {
+        /// The corpus file to check, "-" for stdin
+        #[arg(long)]
+        check: Option<String>,
+    },
+}
+
+fn format_tx(tx: blockchain::Transaction, as_hex: bool) -> String {
+    if as_hex {
+        format!("0x{}\n", hex::encode(tx.as_slice()))
+    } else {
+        let tx: ckb_jsonrpc_types::Transaction = tx.into();
+        serde_json::to_string_pretty(&tx).expect("serialize transaction") + "\n"
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    match cli.command {
+        Command::Inspect { tx } => {
+            let loaded = tx::parse_tx(&tx::read_input(&tx)?)?;
+            print!("{}", inspect(&loaded));
+        }
+        Command::Digest { tx, explain } => {
+            let loaded = tx::parse_tx(&tx::read_input(&tx)?)?;
+            print!("{}", digest::digest(&loaded, explain)?);
+        }
+        Command::Sign {
+            packet,
+            key,
+            output,
+            hex,
+        } => {
+            let packet = sign::parse_packet(&tx::read_input(&packet)?)?;
+            let privkey = sign::parse_privkey(&tx::read_input(&key)?)?;
+            let text = format_tx(sign::sign_packet(&packet, &privkey)?, hex);
+            match output {
+                Some(path) => std::fs::write(&path, text)
+                    .map_err(|e| format!("failed to write {}: {}", path, e))?,
+                None => print!("{}", text),
+            }
+        }
+        Command::Vectors { check: None } => {
+            let corpus =
+                serde_json::to_string_pretty(&vectors::generate()).expect("serialize test vectors");
+            println!("{}", corpus);
+        }
+        Command::Vectors { check: Some(path) } => {
+            let corpus: vectors::Vectors = serde_json::from_str(&tx::read_input(&path)?)
+                .map_err(|e| format!("invalid test vectors: {}", e))?;
+            vectors::check(&corpus)?;
+            println!("{} vector(s) passed", corpus.vectors.len());
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
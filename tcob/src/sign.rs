@@ -0,0 +1,183 @@
+//! Sign a BuildingPacket with a local secp256k1 key and finalize it into a
+//! transaction.
+//!
+//! The key signs every lock script whose args start with the blake160 of its
+//! public key, like the lock demo and secp256k1_blake160_sighash_all. Input
+//! cells in the otx batch get a `SealPair` in their Otx, the others a seal in
+//! SighashAll (or SighashAllOnly for the other lock groups).
+
+use crate::tx::decode_hex;
+use ckb_crypto::secp::{Message as SecpMessage, Privkey};
+use ckb_transaction_cobuild::{
+    building_packet::{check, finalize, packet_signing_message_hash},
+    host::{ckb_hash, otx_batch, otx_signing_message_hash},
+    json,
+    schemas::{
+        basic::{BuildingPacket, BuildingPacketUnion, BuildingPacketV1, SealPair, SealPairVec},
+        blockchain::{Bytes, BytesVec, Transaction},
+        top_level::{WitnessLayout, WitnessLayoutUnion},
+    },
+    Error,
+};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use std::collections::BTreeSet;
+
+///
+/// parse a BuildingPacket in JSON or molecule hex
+///
+pub fn parse_packet(text: &str) -> Result<BuildingPacketV1, String> {
+    let text = text.trim();
+    let packet = if text.starts_with('{') {
+        let packet: json::BuildingPacket =
+            serde_json::from_str(text).map_err(|e| format!("invalid building packet: {}", e))?;
+        packet.into()
+    } else {
+        BuildingPacket::from_slice(&decode_hex(text)?)
+            .map_err(|e| format!("invalid building packet: {}", e))?
+    };
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+    Ok(packet)
+}
+
+///
+/// parse the private key file, hex on the first line like ckb-cli
+///
+pub fn parse_privkey(text: &str) -> Result<Privkey, String> {
+    let line = text.lines().next().unwrap_or_default();
+    let key = decode_hex(line)?;
+    if key.len() != 32 {
+        return Err(format!(
+            "invalid private key: expect 32 bytes, got {}",
+            key.len()
+        ));
+    }
+    let privkey = Privkey::from_slice(&key);
+    privkey
+        .pubkey()
+        .map_err(|e| format!("invalid private key: {}", e))?;
+    Ok(privkey)
+}
+
+fn sign(privkey: &Privkey, message_digest: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let message = SecpMessage::from_slice(message_digest).expect("32 bytes digest");
+    let signature = privkey
+        .sign_recoverable(&message)
+        .map_err(|e| format!("failed to sign: {}", e))?;
+    Ok(signature.serialize())
+}
+
+fn digest_error(e: Error) -> String {
+    format!("failed to calculate the signing message hash: {:?}", e)
+}
+
+///
+/// sign all input cells locked by `privkey` and return the finished transaction
+///
+pub fn sign_packet(packet: &BuildingPacketV1, privkey: &Privkey) -> Result<Transaction, String> {
+    check(packet).map_err(|e| format!("invalid building packet: {:?}", e))?;
+    let pubkey = privkey
+        .pubkey()
+        .map_err(|e| format!("invalid private key: {}", e))?;
+    let pubkey_hash = &ckb_hash(&pubkey.serialize())[..20];
+    let resolved_inputs = packet.resolved_inputs();
+    // lock script hash of every input cell, None if it's not signed by the key
+    let own_locks: Vec<Option<[u8; 32]>> = resolved_inputs
+        .outputs()
+        .into_iter()
+        .map(|cell| {
+            let lock = cell.lock();
+            if lock.args().raw_data().starts_with(pubkey_hash) {
+                Some(ckb_hash(lock.as_slice()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if own_locks.iter().all(Option::is_none) {
+        return Err("no input cell is locked by the key".to_string());
+    }
+
+    // seals of otxs first, they are covered by SighashAll if their witnesses
+    // are beyond the input cells
+    let mut payload = packet.payload();
+    let mut otx_inputs = BTreeSet::new();
+    let batch = match otx_batch(&payload) {
+        Ok(batch) => batch,
+        Err(Error::WrongOtxStart) => Vec::new(),
+        Err(e) => return Err(format!("invalid otx batch: {:?}", e)),
+    };
+    if !batch.is_empty() {
+        let mut witnesses: Vec<Bytes> = payload.witnesses().into_iter().collect();
+        for (index, ranges) in &batch {
+            otx_inputs.extend(ranges.input_cells.clone());
+            let locks: BTreeSet<[u8; 32]> = ranges
+                .input_cells
+                .clone()
+                .filter_map(|i| own_locks[i])
+                .collect();
+            if locks.is_empty() {
+                continue;
+            }
+            let otx = match WitnessLayout::new_unchecked(witnesses[*index].raw_data()).to_enum() {
+                WitnessLayoutUnion::Otx(otx) => otx,
+                _ => unreachable!(),
+            };
+            let message_digest =
+                otx_signing_message_hash(&otx.message(), &payload, &resolved_inputs, ranges)
+                    .map_err(digest_error)?;
+            let seal = sign(privkey, &message_digest)?;
+            let mut seals: Vec<SealPair> = otx
+                .seals()
+                .into_iter()
+                .filter(|pair| {
+                    !locks
+                        .iter()
+                        .any(|lock| pair.script_hash().as_slice() == lock)
+                })
+                .collect();
+            for lock in &locks {
+                seals.push(
+                    SealPair::new_builder()
+                        .script_hash(lock.pack())
+                        .seal(seal.as_slice().pack())
+                        .build(),
+                );
+            }
+            let otx = otx
+                .as_builder()
+                .seals(SealPairVec::new_builder().set(seals).build())
+                .build();
+            witnesses[*index] = WitnessLayout::new_builder()
+                .set(WitnessLayoutUnion::Otx(otx))
+                .build()
+                .as_slice()
+                .pack();
+        }
+        payload = payload
+            .as_builder()
+            .witnesses(BytesVec::new_builder().set(witnesses).build())
+            .build();
+    }
+    let packet = packet.clone().as_builder().payload(payload).build();
+
+    // the first input cell of every lock group outside of the otx batch
+    let mut signed_locks = BTreeSet::new();
+    let mut seal_indexes = Vec::new();
+    for (i, lock) in own_locks.iter().enumerate() {
+        if let Some(lock) = lock {
+            if !otx_inputs.contains(&i) && signed_locks.insert(*lock) {
+                seal_indexes.push(i);
+            }
+        }
+    }
+    if seal_indexes.is_empty() {
+        return Ok(packet.payload());
+    }
+    let message_digest = packet_signing_message_hash(&packet).map_err(digest_error)?;
+    let seal = sign(privkey, &message_digest)?;
+    let seals: Vec<(usize, Vec<u8>)> = seal_indexes
+        .into_iter()
+        .map(|i| (i, seal.clone()))
+        .collect();
+    finalize(&packet, &seals).map_err(|e| format!("failed to finalize: {:?}", e))
+}
@@ -0,0 +1,98 @@
+//! Load a transaction from molecule hex, CKB JSON or a mock transaction JSON
+//! (the format of ckb-debugger and `dapp/src/tmTxDump.json`), the latter also
+//! gives the resolved input cells.
+
+use ckb_jsonrpc_types::{CellOutput, JsonBytes, Transaction};
+use ckb_transaction_cobuild::schemas::{basic::ResolvedInputs, blockchain};
+use ckb_types::prelude::{Builder, Entity, PackVec};
+use serde::Deserialize;
+use std::io::Read;
+
+#[derive(Deserialize)]
+struct MockTransaction {
+    mock_info: MockInfo,
+    tx: Transaction,
+}
+
+#[derive(Deserialize)]
+struct MockInfo {
+    inputs: Vec<MockInput>,
+}
+
+#[derive(Deserialize)]
+struct MockInput {
+    output: CellOutput,
+    data: JsonBytes,
+}
+
+/// A transaction with its resolved input cells if they are known
+pub struct LoadedTx {
+    pub tx: blockchain::Transaction,
+    pub resolved_inputs: Option<ResolvedInputs>,
+}
+
+///
+/// read the file, or stdin if `path` is "-"
+///
+pub fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("failed to read stdin: {}", e))?;
+        Ok(text)
+    } else {
+        std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))
+    }
+}
+
+///
+/// decode hex with optional 0x prefix
+///
+pub fn decode_hex(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim();
+    hex::decode(text.strip_prefix("0x").unwrap_or(text)).map_err(|e| format!("invalid hex: {}", e))
+}
+
+///
+/// parse a transaction in molecule hex, CKB JSON or mock transaction JSON
+///
+pub fn parse_tx(text: &str) -> Result<LoadedTx, String> {
+    let text = text.trim();
+    if !text.starts_with('{') {
+        let tx = blockchain::Transaction::from_slice(&decode_hex(text)?)
+            .map_err(|e| format!("invalid molecule transaction: {}", e))?;
+        return Ok(LoadedTx {
+            tx,
+            resolved_inputs: None,
+        });
+    }
+
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+    if value.get("mock_info").is_some() {
+        let mock: MockTransaction = serde_json::from_value(value)
+            .map_err(|e| format!("invalid mock transaction: {}", e))?;
+        let (outputs, outputs_data): (Vec<blockchain::CellOutput>, Vec<blockchain::Bytes>) = mock
+            .mock_info
+            .inputs
+            .into_iter()
+            .map(|input| (input.output.into(), input.data.into()))
+            .unzip();
+        let resolved_inputs = ResolvedInputs::new_builder()
+            .outputs(outputs.pack())
+            .outputs_data(outputs_data.pack())
+            .build();
+        Ok(LoadedTx {
+            tx: mock.tx.into(),
+            resolved_inputs: Some(resolved_inputs),
+        })
+    } else {
+        let tx: Transaction =
+            serde_json::from_value(value).map_err(|e| format!("invalid transaction: {}", e))?;
+        Ok(LoadedTx {
+            tx: tx.into(),
+            resolved_inputs: None,
+        })
+    }
+}
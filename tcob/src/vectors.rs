@@ -0,0 +1,425 @@
+//! Test vectors of the signing message hashes, for other implementations
+//! (e.g. SDKs in other languages) to prove they calculate the same digests.
+//!
+//! Every vector is a transaction with its resolved inputs, both in molecule
+//! hex, and the digests this library calculates for it: SighashAll with the
+//! message of its SighashAll witness, SighashAllOnly and every otx in its
+//! otx batch. The corpus is versioned, a new version is released if the
+//! digests ever change. `generate` produces the corpus checked in as
+//! `test-vectors/signing-digests.json`, `check` verifies a corpus.
+
+use crate::digest::{otx_message, sighash_all_message};
+use ckb_transaction_cobuild::{
+    host::{ckb_hash, otx_batch, otx_signing_message_hash, signing_message_hash},
+    schemas::{
+        basic::{
+            Action, ActionVec, Message, Otx, OtxStart, ResolvedInputs, SealPair, SealPairVec,
+            SighashAll, SighashAllOnly,
+        },
+        blockchain::{
+            Byte, Byte32Vec, Bytes, BytesVec, CellDep, CellDepVec, CellInput, CellInputVec,
+            CellOutput, CellOutputVec, OutPoint, RawTransaction, Script, ScriptOpt, Transaction,
+        },
+        top_level::{WitnessLayout, WitnessLayoutUnion},
+    },
+    Error,
+};
+use ckb_types::prelude::{Builder, Entity, Pack};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// version of the digests in the corpus
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vectors {
+    pub version: u32,
+    pub vectors: Vec<Vector>,
+}
+
+/// A transaction and its signing message hashes, byte strings are 0x-prefixed hex
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vector {
+    pub name: String,
+    pub description: String,
+    /// molecule serialized `Transaction`
+    pub tx: String,
+    /// molecule serialized `ResolvedInputs`
+    pub resolved_inputs: String,
+    /// molecule serialized `Message` of the SighashAll witness, null if there is none
+    pub message: Option<String>,
+    /// null if there is no SighashAll witness
+    pub sighash_all: Option<String>,
+    pub sighash_all_only: String,
+    pub otxs: Vec<OtxVector>,
+}
+
+/// An otx in the otx batch, the ranges are [start, end) of the items it covers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtxVector {
+    /// index of the Otx witness
+    pub witness: usize,
+    /// molecule serialized `Message` of the Otx witness
+    pub message: String,
+    pub input_cells: [usize; 2],
+    pub output_cells: [usize; 2],
+    pub cell_deps: [usize; 2],
+    pub header_deps: [usize; 2],
+    pub digest: String,
+}
+
+fn to_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(data))
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    hex::decode(text).map_err(|e| format!("invalid hex: {}", e))
+}
+
+fn range(range: &Range<usize>) -> [usize; 2] {
+    [range.start, range.end]
+}
+
+fn digest_error(e: Error) -> String {
+    format!("failed to calculate the signing message hash: {:?}", e)
+}
+
+///
+/// calculate the vector of a transaction
+///
+pub fn vector(
+    name: &str,
+    description: &str,
+    tx: &Transaction,
+    resolved_inputs: &ResolvedInputs,
+) -> Result<Vector, String> {
+    let message = sighash_all_message(tx)?;
+    let sighash_all = match &message {
+        Some(_) => Some(signing_message_hash(&message, tx, resolved_inputs).map_err(digest_error)?),
+        None => None,
+    };
+    let sighash_all_only =
+        signing_message_hash(&None, tx, resolved_inputs).map_err(digest_error)?;
+    let batch = match otx_batch(tx) {
+        Ok(batch) => batch,
+        Err(Error::WrongOtxStart) => Vec::new(),
+        Err(e) => return Err(format!("invalid otx batch: {:?}", e)),
+    };
+    let mut otxs = Vec::with_capacity(batch.len());
+    for (witness, ranges) in batch {
+        let otx_message = otx_message(tx, witness);
+        let digest = otx_signing_message_hash(&otx_message, tx, resolved_inputs, &ranges)
+            .map_err(digest_error)?;
+        otxs.push(OtxVector {
+            witness,
+            message: to_hex(otx_message.as_slice()),
+            input_cells: range(&ranges.input_cells),
+            output_cells: range(&ranges.output_cells),
+            cell_deps: range(&ranges.cell_deps),
+            header_deps: range(&ranges.header_deps),
+            digest: to_hex(&digest),
+        });
+    }
+    Ok(Vector {
+        name: name.to_string(),
+        description: description.to_string(),
+        tx: to_hex(tx.as_slice()),
+        resolved_inputs: to_hex(resolved_inputs.as_slice()),
+        message: message.map(|m| to_hex(m.as_slice())),
+        sighash_all: sighash_all.map(|d| to_hex(&d)),
+        sighash_all_only: to_hex(&sighash_all_only),
+        otxs,
+    })
+}
+
+///
+/// check every vector against the digests calculated by this library
+///
+pub fn check(vectors: &Vectors) -> Result<(), String> {
+    if vectors.version != VERSION {
+        return Err(format!(
+            "unsupported version {}, expected {}",
+            vectors.version, VERSION
+        ));
+    }
+    for v in &vectors.vectors {
+        let tx = Transaction::from_slice(&from_hex(&v.tx)?)
+            .map_err(|e| format!("vector {}: invalid tx: {}", v.name, e))?;
+        let resolved_inputs = ResolvedInputs::from_slice(&from_hex(&v.resolved_inputs)?)
+            .map_err(|e| format!("vector {}: invalid resolved inputs: {}", v.name, e))?;
+        let expected = vector(&v.name, &v.description, &tx, &resolved_inputs)
+            .map_err(|e| format!("vector {}: {}", v.name, e))?;
+        if expected != *v {
+            return Err(format!(
+                "vector {} mismatches, expected:\n{}",
+                v.name,
+                serde_json::to_string_pretty(&expected).unwrap()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn lock(n: u8) -> Script {
+    Script::new_builder()
+        .code_hash([0x11u8; 32].pack())
+        .hash_type(Byte::new(1))
+        .args([n; 20].as_slice().pack())
+        .build()
+}
+
+fn type_script() -> Script {
+    Script::new_builder()
+        .code_hash([0x22u8; 32].pack())
+        .hash_type(Byte::new(2))
+        .args(b"type".as_slice().pack())
+        .build()
+}
+
+fn out_point(tx_hash: u8, index: u32) -> OutPoint {
+    OutPoint::new_builder()
+        .tx_hash([tx_hash; 32].pack())
+        .index(index.pack())
+        .build()
+}
+
+fn cell(capacity: u64, lock: Script, type_: Option<Script>) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(capacity.pack())
+        .lock(lock)
+        .type_(ScriptOpt::new_builder().set(type_).build())
+        .build()
+}
+
+fn message(actions: &[&str]) -> Message {
+    let actions = actions.iter().map(|data| {
+        Action::new_builder()
+            .script_info_hash([0x66u8; 32].pack())
+            .script_hash(ckb_hash(type_script().as_slice()).pack())
+            .data(data.as_bytes().pack())
+            .build()
+    });
+    Message::new_builder()
+        .actions(ActionVec::new_builder().extend(actions).build())
+        .build()
+}
+
+fn layout(layout: WitnessLayoutUnion) -> Vec<u8> {
+    WitnessLayout::new_builder()
+        .set(layout)
+        .build()
+        .as_slice()
+        .to_vec()
+}
+
+fn sighash_all(message: Message) -> Vec<u8> {
+    layout(WitnessLayoutUnion::SighashAll(
+        SighashAll::new_builder()
+            .message(message)
+            .seal([0u8; 65].as_slice().pack())
+            .build(),
+    ))
+}
+
+fn sighash_all_only() -> Vec<u8> {
+    layout(WitnessLayoutUnion::SighashAllOnly(
+        SighashAllOnly::new_builder()
+            .seal([0u8; 65].as_slice().pack())
+            .build(),
+    ))
+}
+
+fn otx_start(start: [u32; 4]) -> Vec<u8> {
+    layout(WitnessLayoutUnion::OtxStart(
+        OtxStart::new_builder()
+            .start_input_cell(start[0].pack())
+            .start_output_cell(start[1].pack())
+            .start_cell_deps(start[2].pack())
+            .start_header_deps(start[3].pack())
+            .build(),
+    ))
+}
+
+fn otx(counts: [u32; 4], message: Message, lock: Script) -> Vec<u8> {
+    let seal = SealPair::new_builder()
+        .script_hash(ckb_hash(lock.as_slice()).pack())
+        .seal([0u8; 65].as_slice().pack())
+        .build();
+    layout(WitnessLayoutUnion::Otx(
+        Otx::new_builder()
+            .input_cells(counts[0].pack())
+            .output_cells(counts[1].pack())
+            .cell_deps(counts[2].pack())
+            .header_deps(counts[3].pack())
+            .message(message)
+            .seals(SealPairVec::new_builder().push(seal).build())
+            .build(),
+    ))
+}
+
+/// The items of a transaction, cells are paired with their data
+struct TxItems {
+    input_cells: Vec<(CellOutput, Vec<u8>)>,
+    outputs: Vec<(CellOutput, Vec<u8>)>,
+    cell_deps: u32,
+    header_deps: u8,
+    witnesses: Vec<Vec<u8>>,
+}
+
+impl TxItems {
+    fn build(self) -> (Transaction, ResolvedInputs) {
+        let inputs = (0..self.input_cells.len()).map(|i| {
+            CellInput::new_builder()
+                .previous_output(out_point(0x33, i as u32))
+                .build()
+        });
+        let cell_deps = (0..self.cell_deps).map(|i| {
+            CellDep::new_builder()
+                .out_point(out_point(0x44, i))
+                .dep_type(Byte::new(1))
+                .build()
+        });
+        let header_deps = (0..self.header_deps).map(|i| [0x55 + i; 32].pack());
+        let data = |cells: &[(CellOutput, Vec<u8>)]| -> BytesVec {
+            BytesVec::new_builder()
+                .extend(
+                    cells
+                        .iter()
+                        .map(|(_, data)| -> Bytes { data.as_slice().pack() }),
+                )
+                .build()
+        };
+        let raw = RawTransaction::new_builder()
+            .cell_deps(CellDepVec::new_builder().extend(cell_deps).build())
+            .header_deps(Byte32Vec::new_builder().extend(header_deps).build())
+            .inputs(CellInputVec::new_builder().extend(inputs).build())
+            .outputs(
+                CellOutputVec::new_builder()
+                    .extend(self.outputs.iter().map(|(cell, _)| cell.clone()))
+                    .build(),
+            )
+            .outputs_data(data(&self.outputs))
+            .build();
+        let witnesses = self
+            .witnesses
+            .iter()
+            .map(|w| -> Bytes { w.as_slice().pack() });
+        let tx = Transaction::new_builder()
+            .raw(raw)
+            .witnesses(BytesVec::new_builder().extend(witnesses).build())
+            .build();
+        let resolved_inputs = ResolvedInputs::new_builder()
+            .outputs(
+                CellOutputVec::new_builder()
+                    .extend(self.input_cells.iter().map(|(cell, _)| cell.clone()))
+                    .build(),
+            )
+            .outputs_data(data(&self.input_cells))
+            .build();
+        (tx, resolved_inputs)
+    }
+}
+
+///
+/// the corpus of this version, calculated by this library
+///
+pub fn generate() -> Vectors {
+    let cases = [
+        (
+            "sighash_all",
+            "one lock group signed with SighashAll, no extra witnesses",
+            TxItems {
+                input_cells: vec![
+                    (cell(100_0000_0000, lock(1), None), vec![]),
+                    (cell(200_0000_0000, lock(1), None), vec![0x01, 0x02]),
+                ],
+                outputs: vec![(cell(299_0000_0000, lock(2), None), vec![0x03])],
+                cell_deps: 1,
+                header_deps: 0,
+                witnesses: vec![sighash_all(message(&["MINT"])), vec![]],
+            },
+        ),
+        (
+            "extra_witnesses",
+            "SighashAll covering the witnesses beyond the input cells, \
+             including an empty one, and an input cell with type script and data",
+            TxItems {
+                input_cells: vec![(
+                    cell(500_0000_0000, lock(1), Some(type_script())),
+                    (0..100).collect(),
+                )],
+                outputs: vec![
+                    (
+                        cell(200_0000_0000, lock(2), Some(type_script())),
+                        vec![0xff; 10],
+                    ),
+                    (cell(299_0000_0000, lock(1), None), vec![]),
+                ],
+                cell_deps: 2,
+                header_deps: 1,
+                witnesses: vec![
+                    sighash_all(message(&["TRANSFER", ""])),
+                    vec![],
+                    vec![0xde, 0xad, 0xbe, 0xef],
+                ],
+            },
+        ),
+        (
+            "sighash_all_only",
+            "two lock groups signed with SighashAllOnly, no SighashAll message",
+            TxItems {
+                input_cells: vec![
+                    (cell(100_0000_0000, lock(1), None), vec![0x01]),
+                    (cell(100_0000_0000, lock(2), None), vec![0x02]),
+                ],
+                outputs: vec![(cell(199_0000_0000, lock(3), None), vec![])],
+                cell_deps: 1,
+                header_deps: 0,
+                witnesses: vec![sighash_all_only(), sighash_all_only()],
+            },
+        ),
+        (
+            "otx",
+            "an otx batch of two otxs after an input cell signed with SighashAll, \
+             the second Otx witness is beyond the input cells",
+            TxItems {
+                input_cells: vec![
+                    (cell(100_0000_0000, lock(3), None), vec![]),
+                    (cell(200_0000_0000, lock(1), None), vec![0x01]),
+                    (
+                        cell(300_0000_0000, lock(2), Some(type_script())),
+                        vec![0x02],
+                    ),
+                ],
+                outputs: vec![
+                    (cell(99_0000_0000, lock(3), None), vec![]),
+                    (cell(199_0000_0000, lock(2), None), vec![0x11]),
+                    (
+                        cell(299_0000_0000, lock(1), Some(type_script())),
+                        vec![0x22],
+                    ),
+                ],
+                cell_deps: 2,
+                header_deps: 1,
+                witnesses: vec![
+                    sighash_all(message(&[])),
+                    otx_start([1, 1, 1, 0]),
+                    otx([1, 1, 1, 0], message(&["SELL"]), lock(1)),
+                    otx([1, 1, 0, 1], message(&["BUY", "PAY"]), lock(2)),
+                ],
+            },
+        ),
+    ];
+    let vectors = cases
+        .into_iter()
+        .map(|(name, description, items)| {
+            let (tx, resolved_inputs) = items.build();
+            vector(name, description, &tx, &resolved_inputs).expect("test vector")
+        })
+        .collect();
+    Vectors {
+        version: VERSION,
+        vectors,
+    }
+}
@@ -1,35 +1,53 @@
-use super::{tx::*, *};
-use ckb_testtool::ckb_crypto::secp::{Generator, Message as SecpMessage};
+use ckb_testtool::ckb_crypto::secp::{Generator, Message as SecpMessage, Privkey};
 use ckb_testtool::{
     bytes::Bytes,
     ckb_error::Error,
     ckb_hash::blake2b_256,
     ckb_types::{
-        core::{DepType, TransactionBuilder},
+        core::{DepType, ScriptHashType, TransactionBuilder, TransactionView},
         packed::*,
         prelude::*,
     },
     context::Context,
 };
-use ckb_transaction_cobuild::blake2b::new_otx_blake2b;
+use ckb_transaction_cobuild::building_packet::{
+    finalize, packet_signing_message_hash, BuildingPacketError, PacketBuilder,
+};
+use ckb_transaction_cobuild::host::{
+    explain_signing_message_hash, otx_signing_message_hash, sighash_all_only_message_hash,
+    signing_message_hash, tx_hash,
+};
+use ckb_transaction_cobuild::json;
+use ckb_transaction_cobuild::native::NativeTransaction;
+use ckb_transaction_cobuild::packet_verifier::{
+    verify_packet_slice, verify_packet_v1, ActionIndex, Finding,
+};
+use ckb_transaction_cobuild::schema_decoder::{decode_action, DecodeError, Schema};
 use ckb_transaction_cobuild::schemas::{
-    basic::{Action, ActionVec, Message, Otx, OtxStart, ResolvedInputs, SealPair, SealPairVec},
+    basic::{
+        Action, ActionVec, BuildingPacketUnion, Message, Otx, OtxStart, ResolvedInputs, ScriptInfo,
+        ScriptInfoVec, SealPair, SealPairVec, SighashAll, SighashAllOnly, String as MolString,
+        Uint32Opt,
+    },
+    spore::{
+        Address, AddressOpt, AddressUnionReader, Melt, Mint, SporeAction, SporeActionUnionReader,
+        Transfer,
+    },
     top_level::{WitnessLayout, WitnessLayoutUnion},
 };
+use ckb_transaction_cobuild::{
+    classify_witness, classify_witnesses, cobuild_entry, fetch_message, fetch_otx_batch,
+    parse_message, parse_otx_message, parse_spore_action, validate_witnesses,
+    Error as CobuildError, OtxBatch, OtxRanges, Violation, WitnessKind,
+};
+use ckb_transaction_cobuild_testkit::{
+    assert_script_error, tx::*, CobuildTxBuilder, Loader, OtxBuilder, TestKey,
+};
 use molecule::prelude::*;
 
 const MAX_CYCLES: u64 = 10_000_000;
-
-// error numbers
-fn assert_script_error(err: Error, err_code: i8) {
-    let error_string = err.to_string();
-    assert!(
-        error_string.contains(format!("error code {} ", err_code).as_str()),
-        "error_string: {}, expected_error_code: {}",
-        error_string,
-        err_code
-    );
-}
+// hashing megabytes of cell data costs far more cycles than the other cases
+const MAX_CYCLES_LARGE_DATA: u64 = 1_000_000_000;
 
 #[test]
 fn test_success_sighash_all() {
@@ -84,6 +102,197 @@ fn test_failed_pubkey() {
     assert_script_error(err, 5); // return Error::AuthError
 }
 
+fn assert_rejected(context: &Context, tx: &TransactionView, mutation: &str, err_code: i8) {
+    println!("mutation: {}", mutation);
+    let err = context.verify_tx(tx, MAX_CYCLES).expect_err(mutation);
+    assert_script_error(err, err_code);
+}
+
+// the SighashAll witness with another message and the same seal
+fn replace_message(witness: Bytes, message: Message) -> Bytes {
+    let sighash_all = match WitnessLayout::from_slice(&witness).unwrap().to_enum() {
+        WitnessLayoutUnion::SighashAll(sighash_all) => sighash_all,
+        _ => panic!("not SighashAll"),
+    };
+    WitnessLayout::new_builder()
+        .set(WitnessLayoutUnion::SighashAll(
+            sighash_all.as_builder().message(message).build(),
+        ))
+        .build()
+        .as_bytes()
+}
+
+#[test]
+fn test_sighash_all_malleability() {
+    // 2 lock groups, SighashAll in the first one and a witness beyond the input cells
+    let mut witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    witnesses.set_with_action(0);
+    let (tx, resolved_inputs, mut context) = gen_tx(&witnesses);
+    let tx = tx
+        .as_advanced_builder()
+        .witness(Bytes::from(vec![0x42; 8]).pack())
+        .build();
+    let tx = sign_tx(&mut witnesses, tx, resolved_inputs.clone());
+    context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+
+    let inputs: Vec<CellInput> = tx.inputs().into_iter().collect();
+    let outputs: Vec<CellOutput> = tx.outputs().into_iter().collect();
+    let outputs_data: Vec<_> = tx.outputs_data().into_iter().collect();
+    let tx_witnesses: Vec<Bytes> = tx.witnesses().into_iter().map(|w| w.raw_data()).collect();
+    let with_witnesses = |witnesses: Vec<Bytes>| {
+        tx.as_advanced_builder()
+            .set_witnesses(witnesses.iter().map(|w| w.pack()).collect())
+            .build()
+    };
+
+    // every input: since, and the cell and its data behind the same out point
+    for i in 0..inputs.len() {
+        let mut mutated = inputs.clone();
+        mutated[i] = inputs[i].clone().as_builder().since(1u64.pack()).build();
+        let mutated = tx.as_advanced_builder().set_inputs(mutated).build();
+        assert_rejected(&context, &mutated, &format!("since of input {}", i), 5);
+
+        let out_point = inputs[i].previous_output();
+        let cell = resolved_inputs.outputs().get(i).unwrap();
+        let data = resolved_inputs.outputs_data().get(i).unwrap().raw_data();
+        let capacity: u64 = cell.capacity().unpack();
+        let mutated_cell = cell
+            .clone()
+            .as_builder()
+            .capacity((capacity + 1).pack())
+            .build();
+        context.create_cell_with_out_point(out_point.clone(), mutated_cell, data.clone());
+        assert_rejected(&context, &tx, &format!("input cell {}", i), 5);
+        let mut mutated_data = data.to_vec();
+        mutated_data.push(0);
+        context.create_cell_with_out_point(out_point.clone(), cell.clone(), mutated_data.into());
+        assert_rejected(&context, &tx, &format!("data of input cell {}", i), 5);
+        context.create_cell_with_out_point(out_point, cell, data);
+    }
+
+    // every output and its data
+    for i in 0..outputs.len() {
+        let mut mutated = outputs.clone();
+        let capacity: u64 = outputs[i].capacity().unpack();
+        mutated[i] = outputs[i]
+            .clone()
+            .as_builder()
+            .capacity((capacity - 1).pack())
+            .build();
+        let mutated = tx.as_advanced_builder().set_outputs(mutated).build();
+        assert_rejected(&context, &mutated, &format!("output {}", i), 5);
+
+        let mut mutated = outputs_data.clone();
+        let mut data = outputs_data[i].raw_data().to_vec();
+        data.push(0);
+        mutated[i] = Bytes::from(data).pack();
+        let mutated = tx.as_advanced_builder().set_outputs_data(mutated).build();
+        assert_rejected(&context, &mutated, &format!("data of output {}", i), 5);
+    }
+
+    // cell deps and header deps
+    let cell_dep = CellDep::new_builder()
+        .out_point(context.deploy_cell(Bytes::from(vec![0])))
+        .dep_type(DepType::Code.into())
+        .build();
+    let mutated = tx.as_advanced_builder().cell_dep(cell_dep).build();
+    assert_rejected(&context, &mutated, "cell deps", 5);
+    let mutated = tx
+        .as_advanced_builder()
+        .header_dep([1u8; 32].pack())
+        .build();
+    assert_rejected(&context, &mutated, "header deps", 5);
+
+    // the message
+    let message = witnesses.get_action().unwrap();
+    let actions = message.actions().into_iter().skip(1).collect::<Vec<_>>();
+    let mutated_message = message
+        .as_builder()
+        .actions(ActionVec::new_builder().set(actions).build())
+        .build();
+    let mut mutated = tx_witnesses.clone();
+    mutated[0] = replace_message(mutated[0].clone(), mutated_message);
+    assert_rejected(&context, &with_witnesses(mutated), "message", 5);
+
+    // witnesses beyond the input cells: changed, removed and appended
+    let extra = inputs.len();
+    let mut mutated = tx_witnesses.clone();
+    mutated[extra] = Bytes::from(vec![0x43; 8]);
+    assert_rejected(&context, &with_witnesses(mutated), "extra witness", 5);
+    let mut mutated = tx_witnesses.clone();
+    mutated.truncate(extra);
+    assert_rejected(
+        &context,
+        &with_witnesses(mutated),
+        "extra witness removed",
+        5,
+    );
+    let mut mutated = tx_witnesses.clone();
+    mutated.push(Bytes::new());
+    assert_rejected(
+        &context,
+        &with_witnesses(mutated),
+        "extra witness appended",
+        5,
+    );
+
+    // the other witnesses in the first group must be empty, they are not
+    // covered by the digest
+    for witness in [
+        Bytes::from(vec![0]),
+        WitnessArgs::new_builder().build().as_bytes(),
+    ] {
+        let mut mutated = tx_witnesses.clone();
+        mutated[1] = witness;
+        assert_rejected(
+            &context,
+            &with_witnesses(mutated),
+            "other witness in group",
+            7,
+        );
+    }
+}
+
+fn large_data(len: usize) -> Bytes {
+    (0..len)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<u8>>()
+        .into()
+}
+
+#[test]
+fn test_success_sighash_all_large_cell_data() {
+    let mut witnesses = MessageWitnesses::new(vec![1], vec![]);
+    witnesses.set_with_action(0);
+    witnesses.message_data[0].input_data = large_data(3 * 1024 * 1024 + 7);
+
+    let (tx, resolved_inputs, context) = gen_tx(&witnesses);
+    let tx = sign_tx(&mut witnesses, tx, resolved_inputs);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES_LARGE_DATA)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_success_sighash_all_only_large_cell_data() {
+    let mut witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    witnesses.message_data[0].input_data = large_data(1024 * 1024);
+    // exactly a multiple of the chunk size used by the library
+    witnesses.message_data[1].input_data = large_data(2 * 1024 * 1024);
+
+    let (tx, resolved_inputs, context) = gen_tx(&witnesses);
+    let tx = sign_tx(&mut witnesses, tx, resolved_inputs);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES_LARGE_DATA)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
 #[test]
 fn test_type_script() {
     let mut witnesses = MessageWitnesses::new(vec![3, 1, 2], vec![]);
@@ -104,16 +313,36 @@ fn test_type_script() {
     println!("consume cycles: {}", cycles);
 }
 
-#[test]
-fn test_success_otx() {
+// one input and two outputs locked by the otx lock, signed in one otx. The
+// OtxStart and the cell counters declared in Otx are given by the caller, the
+// signature always covers the real input and outputs.
+fn gen_single_otx_tx(
+    otx_start: OtxStart,
+    input_cells: u32,
+    output_cells: u32,
+) -> (Context, TransactionView) {
+    gen_single_otx_tx_with_mint(otx_start, input_cells, output_cells, None)
+}
+
+// same as `gen_single_otx_tx`, if `mint_data` is given, the first output is
+// minted with the type demo and the data, and the otx message carries a MINT
+// action of the type demo.
+fn gen_single_otx_tx_with_mint(
+    otx_start: OtxStart,
+    input_cells: u32,
+    output_cells: u32,
+    mint_data: Option<Bytes>,
+) -> (Context, TransactionView) {
     // deploy contract
     let mut context = Context::default();
     let loader = Loader::default();
-    let otx_bin = loader.load_binary("transaction-cobuild-otx-lock-demo");
+    let otx_bin = loader.load_binary("transaction-cobuild-lock-demo");
+    let type_bin = loader.load_binary("transaction-cobuild-type-demo");
     let auth_bin = loader.load_binary("../auth");
     let secp256k1_bin = loader.load_binary("../secp256k1_data_20210801");
 
     let otx_out_point = context.deploy_cell(otx_bin);
+    let type_out_point = context.deploy_cell(type_bin);
     let auth_out_point = context.deploy_cell(auth_bin);
     let secp256k1_out_point = context.deploy_cell(secp256k1_bin);
 
@@ -126,6 +355,9 @@ fn test_success_otx() {
     let lock_script = context
         .build_script(&otx_out_point, pubkey_hash.to_vec().into())
         .expect("script");
+    let type_script = context
+        .build_script(&type_out_point, Default::default())
+        .expect("script");
 
     // prepare cells
     let input_cell = CellOutput::new_builder()
@@ -141,10 +373,12 @@ fn test_success_otx() {
     let input = CellInput::new_builder()
         .previous_output(input_out_point)
         .build();
+    let first_output_type = mint_data.as_ref().map(|_| type_script.clone());
     let outputs = vec![
         CellOutput::new_builder()
             .capacity(500u64.pack())
             .lock(lock_script.clone())
+            .type_(first_output_type.pack())
             .build(),
         CellOutput::new_builder()
             .capacity(500u64.pack())
@@ -152,7 +386,7 @@ fn test_success_otx() {
             .build(),
     ];
 
-    let outputs_data = vec![Bytes::new(); 2];
+    let outputs_data = vec![mint_data.clone().unwrap_or_default(), Bytes::new()];
 
     // build transaction
     let tx = TransactionBuilder::default()
@@ -175,9 +409,28 @@ fn test_success_otx() {
     let tx = context.complete_tx(tx);
 
     // sign otx
-    let message = Message::new_builder().build();
-    let otx_signing_message_hash =
-        generate_otx_signing_message_hash(&message, &tx.data().raw(), &resolved_inputs);
+    let mut actions = ActionVec::new_builder();
+    if mint_data.is_some() {
+        actions = actions.push(
+            Action::new_builder()
+                .script_hash(type_script.calc_script_hash())
+                .data(Bytes::from(b"MINT".to_vec()).pack())
+                .build(),
+        );
+    }
+    let message = Message::new_builder().actions(actions.build()).build();
+    let otx_signing_message_hash = otx_signing_message_hash(
+        &message,
+        &tx.data(),
+        &resolved_inputs,
+        &OtxRanges {
+            input_cells: 0..1,
+            output_cells: 0..2,
+            cell_deps: 0..0,
+            header_deps: 0..0,
+        },
+    )
+    .unwrap();
     let signature = privkey
         .sign_recoverable(&SecpMessage::from_slice(&otx_signing_message_hash).unwrap())
         .unwrap()
@@ -187,12 +440,6 @@ fn test_success_otx() {
         .seal(Bytes::from(signature.to_vec()).pack())
         .build();
 
-    let otx_start = OtxStart::new_builder()
-        .start_cell_deps(0u32.pack())
-        .start_header_deps(0u32.pack())
-        .start_input_cell(0u32.pack())
-        .start_output_cell(0u32.pack())
-        .build();
     let witness1 = WitnessLayout::new_builder()
         .set(WitnessLayoutUnion::OtxStart(otx_start))
         .build()
@@ -202,8 +449,8 @@ fn test_success_otx() {
     let otx = Otx::new_builder()
         .cell_deps(0u32.pack())
         .header_deps(0u32.pack())
-        .input_cells(1u32.pack())
-        .output_cells(2u32.pack())
+        .input_cells(input_cells.pack())
+        .output_cells(output_cells.pack())
         .message(message)
         .seals(SealPairVec::new_builder().push(seal_pair).build())
         .build();
@@ -213,51 +460,1961 @@ fn test_success_otx() {
         .as_bytes()
         .pack();
 
-    // run
     let tx = tx
         .as_advanced_builder()
         .set_witnesses(vec![witness1, witness2])
         .build();
+    (context, tx)
+}
+
+#[test]
+fn test_success_otx() {
+    let otx_start = OtxStart::new_builder()
+        .start_cell_deps(0u32.pack())
+        .start_header_deps(0u32.pack())
+        .start_input_cell(0u32.pack())
+        .start_output_cell(0u32.pack())
+        .build();
+    let (context, tx) = gen_single_otx_tx(otx_start, 1, 2);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_success_otx_type_script() {
+    let (context, tx) =
+        gen_single_otx_tx_with_mint(OtxStart::default(), 1, 2, Some(Bytes::from(vec![1])));
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_otx_type_script_invalid_action() {
+    // MINT with empty data is rejected by the type demo
+    let (context, tx) = gen_single_otx_tx_with_mint(OtxStart::default(), 1, 2, Some(Bytes::new()));
+    let err = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("invalid action");
+    assert_script_error(err, 5); // return Error::InvalidMessage of type demo
+}
+
+#[test]
+fn test_otx_input_cells_out_of_bound() {
+    // the otx claims 2 input cells but the transaction has only 1
+    let (context, tx) = gen_single_otx_tx(OtxStart::default(), 2, 2);
+    let err = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("otx out of bound");
+    assert_script_error(err, 10); // return Error::OtxOutOfBound
+}
+
+#[test]
+fn test_otx_output_cells_out_of_bound() {
+    let (context, tx) = gen_single_otx_tx(OtxStart::default(), 1, 3);
+    let err = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("otx out of bound");
+    assert_script_error(err, 10); // return Error::OtxOutOfBound
+}
+
+#[test]
+fn test_otx_cell_counter_overflow() {
+    let (context, tx) = gen_single_otx_tx(OtxStart::default(), u32::MAX, 2);
+    let err = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("otx out of bound");
+    assert_script_error(err, 10); // return Error::OtxOutOfBound
+}
+
+#[test]
+fn test_otx_start_out_of_bound() {
+    let otx_start = OtxStart::new_builder()
+        .start_input_cell(2u32.pack())
+        .build();
+    let (context, tx) = gen_single_otx_tx(otx_start, 1, 2);
+    let err = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("otx start out of bound");
+    assert_script_error(err, 9); // return Error::OtxStartOutOfBound
+}
+
+fn new_otx_lock(context: &mut Context, otx_out_point: &OutPoint) -> (Privkey, Script) {
+    let privkey = Generator::random_privkey();
+    let pubkey_hash: [u8; 20] = blake2b_256(privkey.pubkey().unwrap().serialize().as_slice())[..20]
+        .try_into()
+        .unwrap();
+    let lock_script = context
+        .build_script(otx_out_point, pubkey_hash.to_vec().into())
+        .expect("script");
+    (privkey, lock_script)
+}
+
+#[test]
+fn test_success_otx_batch() {
+    // deploy contract
+    let mut context = Context::default();
+    let loader = Loader::default();
+    let otx_out_point = context.deploy_cell(loader.load_binary("transaction-cobuild-lock-demo"));
+    let auth_out_point = context.deploy_cell(loader.load_binary("../auth"));
+    let secp256k1_out_point = context.deploy_cell(loader.load_binary("../secp256k1_data_20210801"));
+
+    // prepare scripts
+    let (privkey_a, lock_a) = new_otx_lock(&mut context, &otx_out_point);
+    let (privkey_b, lock_b) = new_otx_lock(&mut context, &otx_out_point);
+
+    // prepare cells, input cells carry data of different lengths
+    let mut inputs = vec![];
+    let mut resolved_outputs = CellOutputVec::new_builder();
+    let mut resolved_outputs_data = BytesVec::new_builder();
+    for (i, lock) in [&lock_a, &lock_b, &lock_a, &lock_a].iter().enumerate() {
+        let cell = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock((*lock).clone())
+            .build();
+        let data = Bytes::from(vec![i as u8; i * 10]);
+        let out_point = context.create_cell(cell.clone(), data.clone());
+        inputs.push(CellInput::new_builder().previous_output(out_point).build());
+        resolved_outputs = resolved_outputs.push(cell);
+        resolved_outputs_data = resolved_outputs_data.push(data.pack());
+    }
+    let resolved_inputs = ResolvedInputs::new_builder()
+        .outputs(resolved_outputs.build())
+        .outputs_data(resolved_outputs_data.build())
+        .build();
+
+    let outputs: Vec<CellOutput> = [&lock_a, &lock_b, &lock_b, &lock_a]
+        .iter()
+        .map(|lock| {
+            CellOutput::new_builder()
+                .capacity(500u64.pack())
+                .lock((*lock).clone())
+                .build()
+        })
+        .collect();
+    let outputs_data: Vec<Bytes> = (0..4).map(|i| Bytes::from(vec![i as u8; i])).collect();
+
+    // build transaction, the header dep is only signed, never loaded by scripts
+    let tx = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(auth_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(secp256k1_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .header_dep(MessageWitnesses::rng_byte32())
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    // the cell dep of otx lock is appended as cell dep 2
+    let tx = context.complete_tx(tx);
+
+    // 3 otxs: a | b | a
+    // (privkey, lock, inputs, outputs, cell deps, header deps)
+    let otxs = vec![
+        (&privkey_a, &lock_a, 0..1, 0..1, 0..2, 0..0),
+        (&privkey_b, &lock_b, 1..2, 1..3, 2..2, 0..1),
+        (&privkey_a, &lock_a, 2..4, 3..4, 2..3, 1..1),
+    ];
+    let mut witnesses = vec![WitnessLayout::new_builder()
+        .set(WitnessLayoutUnion::OtxStart(
+            OtxStart::new_builder().build(),
+        ))
+        .build()
+        .as_bytes()
+        .pack()];
+    for (i, (privkey, lock, inputs, outputs, cell_deps, header_deps)) in
+        otxs.into_iter().enumerate()
+    {
+        let message = Message::new_builder()
+            .actions(
+                ActionVec::new_builder()
+                    .push(
+                        Action::new_builder()
+                            .script_hash(lock.calc_script_hash())
+                            .data(Bytes::from(vec![i as u8]).pack())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build();
+        let otx = Otx::new_builder()
+            .input_cells((inputs.len() as u32).pack())
+            .output_cells((outputs.len() as u32).pack())
+            .cell_deps((cell_deps.len() as u32).pack())
+            .header_deps((header_deps.len() as u32).pack())
+            .message(message.clone());
+        let otx_signing_message_hash = otx_signing_message_hash(
+            &message,
+            &tx.data(),
+            &resolved_inputs,
+            &OtxRanges {
+                input_cells: inputs,
+                output_cells: outputs,
+                cell_deps,
+                header_deps,
+            },
+        )
+        .unwrap();
+        let signature = privkey
+            .sign_recoverable(&SecpMessage::from_slice(&otx_signing_message_hash).unwrap())
+            .unwrap()
+            .serialize();
+        let seal_pair = SealPair::new_builder()
+            .script_hash(lock.calc_script_hash())
+            .seal(Bytes::from(signature.to_vec()).pack())
+            .build();
+        let otx = otx
+            .seals(SealPairVec::new_builder().push(seal_pair).build())
+            .build();
+        witnesses.push(
+            WitnessLayout::new_builder()
+                .set(WitnessLayoutUnion::Otx(otx))
+                .build()
+                .as_bytes()
+                .pack(),
+        );
+    }
+
+    // run
+    let tx = tx.as_advanced_builder().set_witnesses(witnesses).build();
     let cycles = context
         .verify_tx(&tx, MAX_CYCLES)
         .expect("pass verification");
     println!("consume cycles: {}", cycles);
 }
 
-fn generate_otx_signing_message_hash(
-    message: &Message,
-    otx: &RawTransaction,
-    resolved_inputs: &ResolvedInputs,
-) -> [u8; 32] {
-    let mut hasher = new_otx_blake2b();
-    // message
-    hasher.update(message.as_slice());
-    // otx inputs
-    let inputs_len = otx.inputs().len();
-    debug_assert!(inputs_len == resolved_inputs.outputs().len());
-    debug_assert!(inputs_len == resolved_inputs.outputs_data().len());
-    hasher.update(&(inputs_len as u32).to_le_bytes());
-    for i in 0..inputs_len {
-        hasher.update(otx.inputs().get(i).unwrap().as_slice());
-        let input_cell = resolved_inputs.outputs().get(i).unwrap();
-        hasher.update(&input_cell.as_slice());
-        let input_cell_data = resolved_inputs.outputs_data().get(i).unwrap();
-        hasher.update(&(input_cell_data.len() as u32).to_le_bytes());
-        hasher.update(&input_cell_data.raw_data());
+// two inputs locked by the same otx lock: input 0 is added outside of the otx
+// batch and sealed by SighashAllOnly, input 1 is signed in the otx.
+// witnesses: SighashAllOnly | OtxStart | Otx
+fn gen_otx_with_non_otx_input_tx(tamper_non_otx_seal: bool) -> (Context, TransactionView) {
+    // deploy contract
+    let mut context = Context::default();
+    let loader = Loader::default();
+    let otx_out_point = context.deploy_cell(loader.load_binary("transaction-cobuild-lock-demo"));
+    let auth_out_point = context.deploy_cell(loader.load_binary("../auth"));
+    let secp256k1_out_point = context.deploy_cell(loader.load_binary("../secp256k1_data_20210801"));
+
+    // prepare scripts
+    let (privkey, lock) = new_otx_lock(&mut context, &otx_out_point);
+
+    // prepare cells
+    let mut inputs = vec![];
+    let mut resolved_outputs = CellOutputVec::new_builder();
+    let mut resolved_outputs_data = BytesVec::new_builder();
+    for i in 0..2 {
+        let cell = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock.clone())
+            .build();
+        let data = Bytes::from(vec![i as u8; i * 10]);
+        let out_point = context.create_cell(cell.clone(), data.clone());
+        inputs.push(CellInput::new_builder().previous_output(out_point).build());
+        resolved_outputs = resolved_outputs.push(cell);
+        resolved_outputs_data = resolved_outputs_data.push(data.pack());
     }
-    // otx outputs
-    let outputs_len = otx.outputs().len();
-    debug_assert!(outputs_len == otx.outputs_data().len());
-    hasher.update(&(outputs_len as u32).to_le_bytes());
-    for i in 0..outputs_len {
-        hasher.update(otx.outputs().get(i).unwrap().as_slice());
-        hasher.update(otx.outputs_data().get(i).unwrap().as_slice());
+    let resolved_inputs = ResolvedInputs::new_builder()
+        .outputs(resolved_outputs.build())
+        .outputs_data(resolved_outputs_data.build())
+        .build();
+
+    let outputs = vec![
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock.clone())
+            .build(),
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(lock.clone())
+            .build(),
+    ];
+    let outputs_data = vec![Bytes::new(); 2];
+
+    // build transaction
+    let tx = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(auth_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(secp256k1_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .inputs(inputs)
+        .outputs(outputs)
+        .outputs_data(outputs_data.pack())
+        .build();
+    let tx = context.complete_tx(tx);
+
+    // sign otx: input 1 and output 1
+    let message = Message::new_builder().build();
+    let otx_signing_message_hash = otx_signing_message_hash(
+        &message,
+        &tx.data(),
+        &resolved_inputs,
+        &OtxRanges {
+            input_cells: 1..2,
+            output_cells: 1..2,
+            cell_deps: 0..0,
+            header_deps: 0..0,
+        },
+    )
+    .unwrap();
+    let signature = privkey
+        .sign_recoverable(&SecpMessage::from_slice(&otx_signing_message_hash).unwrap())
+        .unwrap()
+        .serialize();
+    let seal_pair = SealPair::new_builder()
+        .script_hash(lock.calc_script_hash())
+        .seal(Bytes::from(signature.to_vec()).pack())
+        .build();
+    let otx_start = OtxStart::new_builder()
+        .start_input_cell(1u32.pack())
+        .start_output_cell(1u32.pack())
+        .build();
+    let otx = Otx::new_builder()
+        .input_cells(1u32.pack())
+        .output_cells(1u32.pack())
+        .message(message)
+        .seals(SealPairVec::new_builder().push(seal_pair).build())
+        .build();
+    let otx_witnesses = vec![
+        WitnessLayout::new_builder()
+            .set(WitnessLayoutUnion::OtxStart(otx_start))
+            .build()
+            .as_bytes()
+            .pack(),
+        WitnessLayout::new_builder()
+            .set(WitnessLayoutUnion::Otx(otx))
+            .build()
+            .as_bytes()
+            .pack(),
+    ];
+
+    // sign the whole transaction, the Otx witness is beyond the inputs and
+    // covered by the signing message hash
+    let tx = tx
+        .as_advanced_builder()
+        .witness(Bytes::new().pack())
+        .witnesses(otx_witnesses.clone())
+        .build();
+    let signing_message_hash = sighash_all_only_message_hash(&tx.data(), &resolved_inputs).unwrap();
+    let mut signature = privkey
+        .sign_recoverable(&SecpMessage::from_slice(&signing_message_hash).unwrap())
+        .unwrap()
+        .serialize();
+    if tamper_non_otx_seal {
+        signature[0] ^= 1;
     }
-    // otx deps (in this unit test, we don't sign any cell and header deps for otx)
-    hasher.update(&0u32.to_le_bytes());
-    hasher.update(&0u32.to_le_bytes());
+    let sighash_all_only = WitnessLayout::new_builder()
+        .set(WitnessLayoutUnion::SighashAllOnly(
+            SighashAllOnly::new_builder()
+                .seal(Bytes::from(signature.to_vec()).pack())
+                .build(),
+        ))
+        .build()
+        .as_bytes()
+        .pack();
+
+    let mut witnesses = vec![sighash_all_only];
+    witnesses.extend(otx_witnesses);
+    let tx = tx.as_advanced_builder().set_witnesses(witnesses).build();
+    (context, tx)
+}
+
+#[test]
+fn test_success_otx_with_non_otx_input() {
+    let (context, tx) = gen_otx_with_non_otx_input_tx(false);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_otx_with_wrong_non_otx_seal() {
+    let (context, tx) = gen_otx_with_non_otx_input_tx(true);
+    let err = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("wrong seal of non-otx input");
+    assert_script_error(err, 5); // return Error::AuthError
+}
+
+// The otx edge cases below assert the error codes of
+// transaction-cobuild-lock-demo. The former transaction-cobuild-otx-lock-demo
+// is merged into it, so these are the codes otx locks return:
+// WrongWitnessLayout = 7, WrongOtxStart = 8, OtxStartOutOfBound = 9,
+// OtxOutOfBound = 10, see `LOCK_DEMO_ERROR_CODES`.
+const LOCK_DEMO_ERROR_CODES: [(&str, i8); 12] = [
+    ("IndexOutOfBound", 1),
+    ("ItemMissing", 2),
+    ("LengthNotEnough", 3),
+    ("Encoding", 4),
+    ("AuthError", 5),
+    ("WrongSighashAll", 6),
+    ("WrongWitnessLayout", 7),
+    ("WrongOtxStart", 8),
+    ("OtxStartOutOfBound", 9),
+    ("OtxOutOfBound", 10),
+    ("NoMatchedAction", 11),
+    ("MultipleMatchedActions", 12),
+];
+
+#[test]
+fn test_lock_demo_error_codes() {
+    // the variants of `Error` in the lock demo, numbered from 1 in order
+    let source = include_str!("../../contracts/transaction-cobuild-lock-demo/src/error.rs");
+    let start = source.find("pub enum Error {").unwrap();
+    let end = start + source[start..].find('}').unwrap();
+    let variants: Vec<&str> = source[start..end]
+        .lines()
+        .skip(1)
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .map(|line| line.split([',', ' ']).next().unwrap())
+        .collect();
+    assert!(source[start..end].contains("IndexOutOfBound = 1,"));
+    let codes: Vec<(&str, i8)> = variants
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, i as i8 + 1))
+        .collect();
+    assert_eq!(codes, LOCK_DEMO_ERROR_CODES);
+}
+
+// a transaction for the lock demo in otx mode: an input cell for every entry
+// of `input_locks`, which are indexes of the locks, and an output cell with
+// the same lock for each of them. Cell deps: auth, secp256k1 data and the
+// lock demo, header deps are random.
+struct OtxTestTx {
+    context: Context,
+    tx: TransactionView,
+    resolved_inputs: ResolvedInputs,
+    locks: Vec<(Privkey, Script)>,
+}
+
+impl OtxTestTx {
+    fn new(locks: usize, input_locks: &[usize], header_deps: usize) -> Self {
+        let mut context = Context::default();
+        let loader = Loader::default();
+        let otx_out_point =
+            context.deploy_cell(loader.load_binary("transaction-cobuild-lock-demo"));
+        let auth_out_point = context.deploy_cell(loader.load_binary("../auth"));
+        let secp256k1_out_point =
+            context.deploy_cell(loader.load_binary("../secp256k1_data_20210801"));
+        let locks: Vec<_> = (0..locks)
+            .map(|_| new_otx_lock(&mut context, &otx_out_point))
+            .collect();
+
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+        let mut resolved_outputs = CellOutputVec::new_builder();
+        let mut resolved_outputs_data = BytesVec::new_builder();
+        for (i, lock) in input_locks.iter().enumerate() {
+            let cell = CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(locks[*lock].1.clone())
+                .build();
+            let data = Bytes::from(vec![i as u8; i]);
+            let out_point = context.create_cell(cell.clone(), data.clone());
+            inputs.push(CellInput::new_builder().previous_output(out_point).build());
+            outputs.push(cell.clone());
+            resolved_outputs = resolved_outputs.push(cell);
+            resolved_outputs_data = resolved_outputs_data.push(data.pack());
+        }
+        let resolved_inputs = ResolvedInputs::new_builder()
+            .outputs(resolved_outputs.build())
+            .outputs_data(resolved_outputs_data.build())
+            .build();
+
+        let tx = TransactionBuilder::default()
+            .cell_dep(
+                CellDep::new_builder()
+                    .out_point(auth_out_point)
+                    .dep_type(DepType::Code.into())
+                    .build(),
+            )
+            .cell_dep(
+                CellDep::new_builder()
+                    .out_point(secp256k1_out_point)
+                    .dep_type(DepType::Code.into())
+                    .build(),
+            )
+            .header_deps((0..header_deps).map(|_| MessageWitnesses::rng_byte32()))
+            .inputs(inputs)
+            .outputs_data(vec![Bytes::new(); outputs.len()].pack())
+            .outputs(outputs)
+            .build();
+        // the cell dep of the lock demo is appended as cell dep 2
+        let tx = context.complete_tx(tx);
+        Self {
+            context,
+            tx,
+            resolved_inputs,
+            locks,
+        }
+    }
+
+    fn script_hash(&self, lock: usize) -> Byte32 {
+        self.locks[lock].1.calc_script_hash()
+    }
+
+    // the signature of `lock` over the otx covering `ranges`, with an empty message
+    fn sign_otx(&self, lock: usize, ranges: &OtxRanges) -> Bytes {
+        let digest = otx_signing_message_hash(
+            &Message::default(),
+            &self.tx.data(),
+            &self.resolved_inputs,
+            ranges,
+        )
+        .unwrap();
+        let signature = self.locks[lock]
+            .0
+            .sign_recoverable(&SecpMessage::from_slice(&digest).unwrap())
+            .unwrap()
+            .serialize();
+        Bytes::from(signature.to_vec())
+    }
+
+    // the Otx witness covering `ranges`, with a seal pair for each (lock, seal)
+    fn otx_witness(&self, ranges: &OtxRanges, seals: &[(usize, Bytes)]) -> Bytes {
+        let seals = seals.iter().map(|(lock, seal)| {
+            SealPair::new_builder()
+                .script_hash(self.script_hash(*lock))
+                .seal(seal.pack())
+                .build()
+        });
+        layout_witness(WitnessLayoutUnion::Otx(
+            Otx::new_builder()
+                .input_cells((ranges.input_cells.len() as u32).pack())
+                .output_cells((ranges.output_cells.len() as u32).pack())
+                .cell_deps((ranges.cell_deps.len() as u32).pack())
+                .header_deps((ranges.header_deps.len() as u32).pack())
+                .seals(SealPairVec::new_builder().extend(seals).build())
+                .build(),
+        ))
+    }
+
+    // the Otx witness covering `ranges` signed by `lock`
+    fn signed_otx_witness(&self, ranges: &OtxRanges, lock: usize) -> Bytes {
+        self.otx_witness(ranges, &[(lock, self.sign_otx(lock, ranges))])
+    }
+
+    // the SighashAllOnly witness of `lock`, signing the transaction with
+    // `witnesses`. Only the witnesses beyond the input cells are signed.
+    fn sighash_all_only_witness(&self, lock: usize, witnesses: &[Bytes]) -> Bytes {
+        let tx = self.with_witnesses(witnesses);
+        let digest = sighash_all_only_message_hash(&tx.data(), &self.resolved_inputs).unwrap();
+        let signature = self.locks[lock]
+            .0
+            .sign_recoverable(&SecpMessage::from_slice(&digest).unwrap())
+            .unwrap()
+            .serialize();
+        layout_witness(WitnessLayoutUnion::SighashAllOnly(
+            SighashAllOnly::new_builder()
+                .seal(Bytes::from(signature.to_vec()).pack())
+                .build(),
+        ))
+    }
+
+    fn with_witnesses(&self, witnesses: &[Bytes]) -> TransactionView {
+        self.tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses.iter().map(|w| w.pack()).collect())
+            .build()
+    }
+
+    fn verify(&self, witnesses: &[Bytes]) -> Result<u64, Error> {
+        self.context
+            .verify_tx(&self.with_witnesses(witnesses), MAX_CYCLES)
+    }
+}
+
+fn otx_ranges(
+    input_cells: std::ops::Range<usize>,
+    output_cells: std::ops::Range<usize>,
+    cell_deps: std::ops::Range<usize>,
+    header_deps: std::ops::Range<usize>,
+) -> OtxRanges {
+    OtxRanges {
+        input_cells,
+        output_cells,
+        cell_deps,
+        header_deps,
+    }
+}
+
+#[test]
+fn test_otx_multiple_otx_start() {
+    let otx_tx = OtxTestTx::new(1, &[0], 0);
+    let otx = otx_tx.signed_otx_witness(&otx_ranges(0..1, 0..1, 0..0, 0..0), 0);
+    let err = otx_tx
+        .verify(&[otx_start_witness(0), otx, otx_start_witness(0)])
+        .expect_err("two OtxStart");
+    assert_script_error(err, 7); // return Error::WrongWitnessLayout of the lock demo
+}
+
+#[test]
+fn test_otx_not_contiguous() {
+    let otx_tx = OtxTestTx::new(1, &[0, 0], 0);
+    let first = otx_tx.signed_otx_witness(&otx_ranges(0..1, 0..1, 0..0, 0..0), 0);
+    let second = otx_tx.signed_otx_witness(&otx_ranges(1..2, 1..2, 0..0, 0..0), 0);
+    let err = otx_tx
+        .verify(&[otx_start_witness(0), first, Bytes::new(), second])
+        .expect_err("gap between Otx witnesses");
+    assert_script_error(err, 7); // return Error::WrongWitnessLayout of the lock demo
+}
+
+#[test]
+fn test_otx_before_otx_start() {
+    let otx_tx = OtxTestTx::new(1, &[0], 0);
+    let otx = otx_tx.signed_otx_witness(&otx_ranges(0..1, 0..1, 0..0, 0..0), 0);
+    let err = otx_tx
+        .verify(&[otx, otx_start_witness(0)])
+        .expect_err("Otx before OtxStart");
+    assert_script_error(err, 7); // return Error::WrongWitnessLayout of the lock demo
+}
+
+#[test]
+fn test_otx_deps_out_of_bound() {
+    // 3 cell deps and no header dep in the transaction
+    let otx_tx = OtxTestTx::new(1, &[0], 0);
+    let seal = (0, Bytes::from(vec![0u8; 65]));
+    for ranges in [
+        otx_ranges(0..1, 0..1, 0..4, 0..0),
+        otx_ranges(0..1, 0..1, 0..0, 0..1),
+    ] {
+        let otx = otx_tx.otx_witness(&ranges, &[seal.clone()]);
+        let err = otx_tx
+            .verify(&[otx_start_witness(0), otx])
+            .expect_err("otx out of bound");
+        assert_script_error(err, 10); // return Error::OtxOutOfBound of the lock demo
+    }
+}
+
+#[test]
+fn test_otx_seal_for_wrong_script_hash() {
+    let otx_tx = OtxTestTx::new(2, &[0], 0);
+    let ranges = otx_ranges(0..1, 0..1, 0..0, 0..0);
+    // signed by the lock of the input cell, but the seal pair names another lock
+    let otx = otx_tx.otx_witness(&ranges, &[(1, otx_tx.sign_otx(0, &ranges))]);
+    let err = otx_tx
+        .verify(&[otx_start_witness(0), otx])
+        .expect_err("no seal for the lock");
+    assert_script_error(err, 5); // return Error::AuthError of the lock demo
+}
+
+#[test]
+fn test_otx_same_lock_in_several_otxs() {
+    // otxs: a | b | a
+    let otx_tx = OtxTestTx::new(2, &[0, 1, 0], 0);
+    let ranges = [
+        otx_ranges(0..1, 0..1, 0..0, 0..0),
+        otx_ranges(1..2, 1..2, 0..0, 0..0),
+        otx_ranges(2..3, 2..3, 0..0, 0..0),
+    ];
+    let witnesses = [
+        otx_start_witness(0),
+        otx_tx.signed_otx_witness(&ranges[0], 0),
+        otx_tx.signed_otx_witness(&ranges[1], 1),
+        otx_tx.signed_otx_witness(&ranges[2], 0),
+    ];
+    otx_tx.verify(&witnesses).expect("pass verification");
+
+    // every otx of the lock is verified, not only the first one
+    let mut tampered = witnesses.clone();
+    tampered[3] = otx_tx.otx_witness(&ranges[2], &[(0, otx_tx.sign_otx(0, &ranges[0]))]);
+    let err = otx_tx
+        .verify(&tampered)
+        .expect_err("wrong seal of the second otx");
+    assert_script_error(err, 5); // return Error::AuthError of the lock demo
+}
+
+#[test]
+fn test_otx_signs_cell_and_header_deps() {
+    let mut otx_tx = OtxTestTx::new(1, &[0], 1);
+    let ranges = otx_ranges(0..1, 0..1, 0..3, 0..1);
+    let witnesses = [otx_start_witness(0), otx_tx.signed_otx_witness(&ranges, 0)];
+    otx_tx.verify(&witnesses).expect("pass verification");
+
+    // the header dep is signed by the otx
+    let tx = otx_tx
+        .with_witnesses(&witnesses)
+        .as_advanced_builder()
+        .set_header_deps(vec![MessageWitnesses::rng_byte32()])
+        .build();
+    let err = otx_tx
+        .context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect_err("header dep changed");
+    assert_script_error(err, 5); // return Error::AuthError of the lock demo
+
+    // a cell dep appended after the ones in the otx is not
+    let out_point = otx_tx.context.deploy_cell(Bytes::from(vec![0]));
+    let tx = otx_tx
+        .with_witnesses(&witnesses)
+        .as_advanced_builder()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .build();
+    otx_tx
+        .context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+}
+
+#[test]
+fn test_otx_followed_by_non_otx_input() {
+    // the otx covers input 0 and 1, input 2 is sealed by SighashAllOnly at
+    // its own index, right after the otx batch
+    let otx_tx = OtxTestTx::new(1, &[0, 0, 0], 0);
+    let otx = otx_tx.signed_otx_witness(&otx_ranges(0..2, 0..2, 0..0, 0..0), 0);
+    let mut witnesses = vec![otx_start_witness(0), otx, Bytes::new()];
+    witnesses[2] = otx_tx.sighash_all_only_witness(0, &witnesses);
+    otx_tx.verify(&witnesses).expect("pass verification");
+
+    // without the seal of the input cell outside of the otx batch
+    witnesses[2] = Bytes::new();
+    let err = otx_tx
+        .verify(&witnesses)
+        .expect_err("non-otx input cell without seal");
+    assert_script_error(err, 4); // return Error::Encoding of the lock demo
+}
+
+fn layout_witness(layout: WitnessLayoutUnion) -> Bytes {
+    WitnessLayout::new_builder().set(layout).build().as_bytes()
+}
+
+fn otx_witness(input_cells: u32) -> Bytes {
+    layout_witness(WitnessLayoutUnion::Otx(
+        Otx::new_builder().input_cells(input_cells.pack()).build(),
+    ))
+}
+
+fn otx_start_witness(start_input_cell: u32) -> Bytes {
+    layout_witness(WitnessLayoutUnion::OtxStart(
+        OtxStart::new_builder()
+            .start_input_cell(start_input_cell.pack())
+            .build(),
+    ))
+}
+
+#[test]
+fn test_validate_witnesses() {
+    let witnesses = vec![
+        layout_witness(WitnessLayoutUnion::SighashAll(Default::default())),
+        layout_witness(WitnessLayoutUnion::SighashAllOnly(Default::default())),
+        otx_start_witness(3),
+        otx_witness(1),
+        otx_witness(0),
+        Bytes::new(),
+    ];
+    let report = validate_witnesses(&witnesses, 4);
+    assert!(report.is_valid(), "{:?}", report.violations);
+    assert_eq!(report.sighash_all, Some(0));
+    assert_eq!(report.sighash_all_only, vec![1]);
+    assert_eq!(report.otx_start, Some(2));
+    assert_eq!(report.otx, 3..5);
+    assert_eq!(report.otx_input_cells, 3..4);
+}
+
+#[test]
+fn test_validate_witnesses_violations() {
+    let mut malformed = layout_witness(WitnessLayoutUnion::SighashAll(Default::default())).to_vec();
+    malformed.truncate(malformed.len() - 1);
+    let witnesses = vec![
+        layout_witness(WitnessLayoutUnion::SighashAll(Default::default())),
+        otx_witness(1),
+        layout_witness(WitnessLayoutUnion::SighashAll(Default::default())),
+        Bytes::from(malformed),
+        otx_start_witness(0),
+        // not a WitnessLayout, it's ignored
+        Bytes::from(vec![0u8; 16]),
+        layout_witness(WitnessLayoutUnion::SighashAllOnly(Default::default())),
+    ];
+    let report = validate_witnesses(&witnesses, 5);
+    assert_eq!(
+        report.violations,
+        vec![
+            Violation::OtxWithoutOtxStart(1),
+            Violation::MultipleSighashAll {
+                first: 0,
+                second: 2
+            },
+            Violation::MalformedWitnessLayout(3),
+            Violation::SighashBeyondInputs(6),
+            Violation::OtxStartWithoutOtx(4),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_witnesses_otx_conflicts() {
+    let witnesses = vec![
+        layout_witness(WitnessLayoutUnion::SighashAllOnly(Default::default())),
+        otx_start_witness(0),
+        otx_witness(2),
+        Bytes::new(),
+        otx_witness(1),
+        otx_start_witness(0),
+    ];
+    let report = validate_witnesses(&witnesses, 6);
+    assert_eq!(
+        report.violations,
+        vec![
+            Violation::OtxNotContiguous(4),
+            Violation::MultipleOtxStart(5),
+            Violation::SighashAtOtxInput(0),
+        ]
+    );
+}
+
+#[test]
+fn test_classify_witnesses() {
+    let seal = Bytes::from(vec![7u8; 65]);
+    let message = Message::new_builder()
+        .actions(
+            ActionVec::new_builder()
+                .push(
+                    Action::new_builder()
+                        .data(Bytes::from(b"MINT".to_vec()).pack())
+                        .build(),
+                )
+                .build(),
+        )
+        .build();
+    let sighash_all = layout_witness(WitnessLayoutUnion::SighashAll(
+        SighashAll::new_builder()
+            .seal(seal.pack())
+            .message(message.clone())
+            .build(),
+    ));
+    let witness_args = WitnessArgs::new_builder()
+        .lock(Some(seal.clone()).pack())
+        .build()
+        .as_bytes();
+    let witnesses = vec![
+        sighash_all.clone(),
+        witness_args.clone(),
+        otx_start_witness(0),
+        Bytes::new(),
+        Bytes::from(vec![1u8, 2, 3]),
+    ];
+    let kinds = classify_witnesses(&witnesses);
+
+    match &kinds[0] {
+        WitnessKind::SighashAll {
+            seal: seal_range,
+            message: message_range,
+        } => {
+            assert_eq!(&sighash_all[seal_range.clone()], &seal[..]);
+            assert_eq!(&sighash_all[message_range.clone()], message.as_slice());
+        }
+        kind => panic!("unexpected {:?}", kind),
+    }
+    match &kinds[1] {
+        WitnessKind::WitnessArgs {
+            lock: Some(lock),
+            input_type: None,
+            output_type: None,
+        } => assert_eq!(&witness_args[lock.clone()], &seal[..]),
+        kind => panic!("unexpected {:?}", kind),
+    }
+    assert!(matches!(kinds[2], WitnessKind::OtxStart { .. }));
+    assert_eq!(kinds[3], WitnessKind::Empty);
+    assert_eq!(kinds[4], WitnessKind::Unknown);
+}
+
+// sign the groups with the legacy WitnessArgs, `privkeys` are used for the groups in order
+fn sign_legacy_tx(tx: TransactionView, groups: &[usize], privkeys: &[&Privkey]) -> TransactionView {
+    let placeholder = WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+        .build()
+        .as_bytes();
+    let mut witnesses = vec![];
+    for group_size in groups {
+        witnesses.push(placeholder.clone());
+        witnesses.extend(vec![Bytes::new(); group_size - 1]);
+    }
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(witnesses.iter().map(|w| w.pack()).collect())
+        .build();
+
+    let mut start = 0;
+    for (group_size, privkey) in groups.iter().zip(privkeys) {
+        let group: Vec<usize> = (start..start + group_size).collect();
+        let message_hash = generate_legacy_signing_message_hash(&tx, &group);
+        let signature = privkey
+            .sign_recoverable(&SecpMessage::from_slice(&message_hash).unwrap())
+            .unwrap()
+            .serialize();
+        witnesses[start] = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(signature)).pack())
+            .build()
+            .as_bytes();
+        start += group_size;
+    }
+    tx.as_advanced_builder()
+        .set_witnesses(witnesses.iter().map(|w| w.pack()).collect())
+        .build()
+}
+
+#[test]
+fn test_success_legacy_witness_args() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, _, context) = gen_tx(&witnesses);
+    let privkeys: Vec<&Privkey> = witnesses.message_data.iter().map(|d| &d.privkey).collect();
+    let tx = sign_legacy_tx(tx, &[2, 1], &privkeys);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_legacy_witness_args_wrong_key() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, _, context) = gen_tx(&witnesses);
+    // the second group is signed by the key of the first group
+    let privkey = &witnesses.message_data[0].privkey;
+    let tx = sign_legacy_tx(tx, &[2, 1], &[privkey, privkey]);
+    let err = context.verify_tx(&tx, MAX_CYCLES).expect_err("wrong key");
+    assert_script_error(err, 5); // return Error::AuthError
+}
+
+#[test]
+fn test_host_tx_hash() {
+    let witnesses = MessageWitnesses::new(vec![1, 2], vec![]);
+    let (tx, _, _) = gen_tx(&witnesses);
+    assert_eq!(&tx_hash(&tx.data()), tx.hash().as_slice());
+}
+
+#[test]
+fn test_host_missing_resolved_input() {
+    let witnesses = MessageWitnesses::new(vec![1, 2], vec![]);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    // only the first input cell is resolved
+    let outputs = resolved_inputs
+        .outputs()
+        .as_builder()
+        .set(vec![resolved_inputs.outputs().get(0).unwrap()])
+        .build();
+    let resolved_inputs = resolved_inputs.as_builder().outputs(outputs).build();
+    assert!(sighash_all_only_message_hash(&tx.data(), &resolved_inputs).is_err());
+}
+
+fn type_demo_script_info(tx: &TransactionView) -> ScriptInfo {
+    let type_script = tx.outputs().get(1).unwrap().type_().to_opt().unwrap();
+    ScriptInfo::new_builder()
+        .script_hash(type_script.calc_script_hash())
+        .build()
+}
+
+fn new_packet_builder(tx: &TransactionView, resolved_inputs: &ResolvedInputs) -> PacketBuilder {
+    let mut builder = PacketBuilder::new(tx.data());
+    for (output, data) in resolved_inputs
+        .outputs()
+        .into_iter()
+        .zip(resolved_inputs.outputs_data())
+    {
+        builder = builder.resolved_input(output, &data.raw_data());
+    }
+    builder
+}
+
+#[test]
+fn test_success_building_packet() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, resolved_inputs, context) = gen_tx(&witnesses);
+    let packet = new_packet_builder(&tx, &resolved_inputs)
+        .action(type_demo_script_info(&tx), b"MINT")
+        .change_output(0)
+        .build()
+        .expect("consistent building packet");
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+
+    let message_hash = packet_signing_message_hash(&packet).unwrap();
+    let seals: Vec<(usize, Vec<u8>)> = [0, 2]
+        .into_iter()
+        .zip(&witnesses.message_data)
+        .map(|(index, data)| {
+            let signature = data
+                .privkey
+                .sign_recoverable(&SecpMessage::from_slice(&message_hash).unwrap())
+                .unwrap()
+                .serialize();
+            (index, signature.to_vec())
+        })
+        .collect();
+    let tx = finalize(&packet, &seals).expect("finalize").into_view();
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_building_packet_inconsistent() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+
+    let err = PacketBuilder::new(tx.data()).build().unwrap_err();
+    assert_eq!(
+        err,
+        BuildingPacketError::ResolvedInputsMismatch {
+            inputs: 3,
+            resolved: 0
+        }
+    );
+
+    let err = new_packet_builder(&tx, &resolved_inputs)
+        .change_output(2)
+        .build()
+        .unwrap_err();
+    assert_eq!(err, BuildingPacketError::ChangeOutputOutOfBound(2));
+
+    // the type script isn't a lock script of any input cell
+    let err = new_packet_builder(&tx, &resolved_inputs)
+        .action(type_demo_script_info(&tx), b"MINT")
+        .lock_action(type_demo_script_info(&tx), b"")
+        .build()
+        .unwrap_err();
+    assert_eq!(err, BuildingPacketError::UnknownScript(1));
+
+    let packet = new_packet_builder(&tx, &resolved_inputs).build().unwrap();
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+    let err = finalize(&packet, &[(3, vec![0u8; 65])]).unwrap_err();
+    assert_eq!(err, BuildingPacketError::SealOutOfBound(3));
+    let err = finalize(&packet, &[(0, vec![0u8; 65]), (0, vec![1u8; 65])]).unwrap_err();
+    assert_eq!(err, BuildingPacketError::DuplicateSeal(0));
+
+    // the otx batch in witness 1 isn't overwritten by a seal
+    let otx_start = layout_witness(WitnessLayoutUnion::OtxStart(OtxStart::default()));
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![
+            Bytes::new().pack(),
+            otx_start.pack(),
+            otx_witness(1).pack(),
+        ])
+        .build();
+    let packet = new_packet_builder(&tx, &resolved_inputs).build().unwrap();
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+    let err = finalize(&packet, &[(0, vec![0u8; 65]), (1, vec![1u8; 65])]).unwrap_err();
+    assert_eq!(err, BuildingPacketError::WitnessOccupied(1));
+    let tx = finalize(&packet, &[(0, vec![0u8; 65])]).unwrap();
+    assert_eq!(tx.witnesses().get(1).unwrap().raw_data(), otx_start);
+}
+
+#[test]
+fn test_finalize_message_seals() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    // the message would be lost without a seal
+    let packet = new_packet_builder(&tx, &resolved_inputs)
+        .action(type_demo_script_info(&tx), b"MINT")
+        .build()
+        .unwrap();
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+    let err = finalize(&packet, &[]).unwrap_err();
+    assert_eq!(err, BuildingPacketError::MissingSeal);
+
+    // witness 2 is SighashAll signed before, it's kept if it has no seal
+    let sighash_all = layout_witness(WitnessLayoutUnion::SighashAll(SighashAll::default()));
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![
+            Bytes::new().pack(),
+            Bytes::new().pack(),
+            sighash_all.pack(),
+        ])
+        .build();
+    let packet = new_packet_builder(&tx, &resolved_inputs)
+        .action(type_demo_script_info(&tx), b"MINT")
+        .build()
+        .unwrap();
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+    let err = finalize(&packet, &[(0, vec![0u8; 65])]).unwrap_err();
+    assert_eq!(err, BuildingPacketError::DuplicateSighashAll(2));
+    let tx = finalize(&packet, &[(0, vec![0u8; 65]), (2, vec![1u8; 65])]).unwrap();
+    assert!(matches!(
+        classify_witness(&tx.witnesses().get(2).unwrap().raw_data()),
+        WitnessKind::SighashAllOnly { .. }
+    ));
+}
+
+#[test]
+fn test_verify_packet() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    let packet = new_packet_builder(&tx, &resolved_inputs)
+        .action(type_demo_script_info(&tx), b"MINT")
+        .change_output(0)
+        .build()
+        .unwrap();
+    assert_eq!(verify_packet_slice(packet.as_slice()), Ok(vec![]));
+    assert!(verify_packet_slice(&packet.as_slice()[1..]).is_err());
+
+    let BuildingPacketUnion::BuildingPacketV1(packet) = packet.to_enum();
+    let script_info = packet.script_infos().get(0).unwrap();
+    let action = packet.message().actions().get(0).unwrap();
+    let unknown_action = action
+        .clone()
+        .as_builder()
+        .script_info_hash(Byte32::default())
+        .build();
+    let mismatch_action = action.as_builder().script_hash([1u8; 32].pack()).build();
+    let packet = packet
+        .clone()
+        .as_builder()
+        .resolved_inputs(
+            packet
+                .resolved_inputs()
+                .as_builder()
+                .outputs(CellOutputVec::default())
+                .build(),
+        )
+        .change_output(Uint32Opt::new_builder().set(Some(3u32.pack())).build())
+        .script_infos(
+            ScriptInfoVec::new_builder()
+                .set(vec![script_info.clone(), script_info])
+                .build(),
+        )
+        .lock_actions(
+            ActionVec::new_builder()
+                .set(vec![unknown_action, mismatch_action])
+                .build(),
+        )
+        .build();
+    assert_eq!(
+        verify_packet_v1(packet.as_reader()),
+        vec![
+            Finding::ResolvedInputsMismatch {
+                inputs: 3,
+                resolved: 0
+            },
+            Finding::ResolvedInputsDataMismatch {
+                outputs: 0,
+                outputs_data: 3
+            },
+            Finding::ChangeOutputOutOfBound(3),
+            Finding::DuplicateScriptInfo(1),
+            // no resolved input, so the type script of output is the only known script
+            Finding::MissingScriptInfo(ActionIndex::Lock(0)),
+            Finding::UnknownScript(ActionIndex::Lock(0)),
+            Finding::ScriptHashMismatch {
+                action: ActionIndex::Lock(1),
+                script_info: 0
+            },
+            Finding::UnknownScript(ActionIndex::Lock(1)),
+        ]
+    );
+}
+
+const DAPP_TX_DUMP: &str = include_str!("../../dapp/src/tmTxDump.json");
+
+fn json_bytes(value: &serde_json::Value) -> Vec<u8> {
+    hex::decode(value.as_str().unwrap().trim_start_matches("0x")).unwrap()
+}
+
+fn json_byte32(value: &serde_json::Value) -> Byte32 {
+    let hash: [u8; 32] = json_bytes(value).try_into().unwrap();
+    hash.pack()
+}
+
+fn json_script(value: &serde_json::Value) -> Script {
+    let hash_type = match value["hash_type"].as_str().unwrap() {
+        "data" => ScriptHashType::Data,
+        "type" => ScriptHashType::Type,
+        "data1" => ScriptHashType::Data1,
+        other => panic!("unknown hash type {}", other),
+    };
+    Script::new_builder()
+        .code_hash(json_byte32(&value["code_hash"]))
+        .hash_type(hash_type.into())
+        .args(json_bytes(&value["args"]).as_slice().pack())
+        .build()
+}
+
+// the witness in dapp's dump is SighashAll with seal before message, the
+// order used by the dapp's molecule definitions
+fn dapp_message(witness: &[u8]) -> Message {
+    let sighash_all = &witness[molecule::NUMBER_SIZE..];
+    let message_start = molecule::unpack_number(&sighash_all[8..]) as usize;
+    Message::from_slice(&sighash_all[message_start..]).unwrap()
+}
+
+#[test]
+fn test_spore_action_dapp_dump() {
+    let dump: serde_json::Value = serde_json::from_str(DAPP_TX_DUMP).unwrap();
+    let tx = &dump["tx"];
+    let message = dapp_message(&json_bytes(&tx["witnesses"][0]));
+    let action = message.actions().get(0).unwrap();
+    let output = &tx["outputs"][0];
+
+    // decode
+    let spore_action = parse_spore_action(action.as_reader()).unwrap();
+    let transfer = match spore_action.to_enum() {
+        SporeActionUnionReader::Transfer(transfer) => transfer,
+        other => panic!("unexpected spore action {}", other),
+    };
+    assert_eq!(
+        transfer.nft_id().as_slice(),
+        json_bytes(&output["type"]["args"]).as_slice()
+    );
+    assert!(transfer.from().is_none());
+    let AddressUnionReader::Script(to) = transfer.to().to_opt().unwrap().to_enum();
+    assert_eq!(to.as_slice(), json_script(&output["lock"]).as_slice());
+
+    // encode
+    let to = Address::new_builder()
+        .set(json_script(&output["lock"]))
+        .build();
+    let transfer = Transfer::new_builder()
+        .nft_id(json_byte32(&output["type"]["args"]))
+        .to(AddressOpt::new_builder().set(Some(to)).build())
+        .build();
+    let spore_action = SporeAction::new_builder().set(transfer).build();
+    assert_eq!(spore_action.as_slice(), action.data().raw_data().as_ref());
+}
+
+#[test]
+fn test_spore_action_round_trip() {
+    let mint = Mint::new_builder()
+        .id([1u8; 32].pack())
+        .content_hash([2u8; 32].pack())
+        .build();
+    let melt = Melt::new_builder().id([3u8; 32].pack()).build();
+    for spore_action in [
+        SporeAction::new_builder().set(mint).build(),
+        SporeAction::new_builder().set(melt).build(),
+    ] {
+        let action = Action::new_builder()
+            .data(spore_action.as_slice().pack())
+            .build();
+        let decoded = parse_spore_action(action.as_reader()).unwrap();
+        assert_eq!(decoded.as_slice(), spore_action.as_slice());
+    }
+
+    let action = Action::new_builder()
+        .data(b"MINT".as_slice().pack())
+        .build();
+    assert!(parse_spore_action(action.as_reader()).is_err());
+}
+
+fn mol_string(s: &str) -> MolString {
+    MolString::new_builder()
+        .set(s.bytes().map(Byte::new).collect())
+        .build()
+}
+
+#[test]
+fn test_decode_action_spore() {
+    let dump: serde_json::Value = serde_json::from_str(DAPP_TX_DUMP).unwrap();
+    let tx = &dump["tx"];
+    let message = dapp_message(&json_bytes(&tx["witnesses"][0]));
+    let action = message.actions().get(0).unwrap();
+    let output = &tx["outputs"][0];
+
+    let script_info = ScriptInfo::new_builder()
+        .schema(mol_string(include_str!("../../schemas/spore.mol")))
+        .message_type(mol_string("SporeAction"))
+        .build();
+    let value = decode_action(script_info.as_reader(), action.as_reader()).unwrap();
+    let expected = format!(
+        concat!(
+            r#"{{"type":"Transfer","value":{{"nft_id":"{}","from":null,"#,
+            r#""to":{{"type":"Script","value":{{"code_hash":"{}","hash_type":"0x02","args":"{}"}}}}}}}}"#
+        ),
+        output["type"]["args"].as_str().unwrap(),
+        output["lock"]["code_hash"].as_str().unwrap(),
+        output["lock"]["args"].as_str().unwrap(),
+    );
+    assert_eq!(value.to_json(), expected);
+    // the output is valid JSON
+    serde_json::from_str::<serde_json::Value>(&value.to_json()).unwrap();
+}
+
+#[test]
+fn test_decode_custom_schema() {
+    let schema = Schema::parse(
+        r#"
+        import blockchain;
+
+        /* an action with every kind of type */
+        struct Point { x: Uint32, y: byte }
+        array Line [Point; 2];
+        vector Points <Point>;
+        vector Names <Bytes>;
+        option NameOpt (Bytes);
+        union Shape { Line: 7, Points }
+        table Draw {
+            shape: Shape,
+            names: Names,
+            name: NameOpt,
+        }
+        "#,
+    )
+    .unwrap();
+    let point = |x: u32, y: u8| [x.to_le_bytes().as_slice(), [y].as_slice()].concat();
+    let names = BytesVec::new_builder()
+        .push(b"a".as_slice().pack())
+        .push(b"bc".as_slice().pack())
+        .build();
+    let shape = [
+        8u32.to_le_bytes().as_slice(),
+        1u32.to_le_bytes().as_slice(),
+        point(1, 2).as_slice(),
+    ]
+    .concat();
+    let mut draw = Vec::new();
+    let header = 4 * 4;
+    let offsets = [
+        header,
+        header + shape.len(),
+        header + shape.len() + names.as_slice().len(),
+    ];
+    draw.extend_from_slice(&(offsets[2] as u32).to_le_bytes());
+    for offset in offsets {
+        draw.extend_from_slice(&(offset as u32).to_le_bytes());
+    }
+    draw.extend_from_slice(&shape);
+    draw.extend_from_slice(names.as_slice());
+    assert_eq!(
+        schema.decode("Draw", &draw).unwrap().to_json(),
+        concat!(
+            r#"{"shape":{"type":"Points","value":[{"x":"0x01000000","y":"0x02"}]},"#,
+            r#""names":["0x61","0x6263"],"name":null}"#
+        )
+    );
+    let line = [point(1, 2), point(3, 4)].concat();
+    assert_eq!(
+        schema.decode("Line", &line).unwrap().to_json(),
+        r#"[{"x":"0x01000000","y":"0x02"},{"x":"0x03000000","y":"0x04"}]"#
+    );
+
+    // malformed data
+    assert_eq!(
+        schema.decode("Line", &line[1..]),
+        Err(DecodeError::Malformed("Line".to_string()))
+    );
+    assert_eq!(
+        schema.decode("Draw", &draw[..draw.len() - 1]),
+        Err(DecodeError::Malformed("Draw".to_string()))
+    );
+    assert_eq!(
+        schema.decode("Shape", &0u32.to_le_bytes()),
+        Err(DecodeError::UnknownUnionItem {
+            union: "Shape".to_string(),
+            item_id: 0
+        })
+    );
+    assert_eq!(
+        schema.decode("Circle", &[]),
+        Err(DecodeError::UnknownType("Circle".to_string()))
+    );
+
+    // broken schemas
+    assert_eq!(
+        Schema::parse("import nervos;").unwrap_err(),
+        DecodeError::UnknownImport("nervos".to_string())
+    );
+    assert_eq!(
+        Schema::parse("table A { b: B }").unwrap_err(),
+        DecodeError::UnknownType("B".to_string())
+    );
+    assert_eq!(
+        Schema::parse("vector A <byte>;\nstruct B { a: A }").unwrap_err(),
+        DecodeError::NotFixedSize("B".to_string())
+    );
+    // the item count of a fixvec of empty items would be unlimited by the data
+    assert_eq!(
+        Schema::parse("array A [byte; 0];\nvector B <A>;").unwrap_err(),
+        DecodeError::ZeroSize("A".to_string())
+    );
+    assert_eq!(
+        Schema::parse("struct A {}\nvector B <A>;").unwrap_err(),
+        DecodeError::ZeroSize("A".to_string())
+    );
+    assert_eq!(
+        Schema::parse("struct A {}\nstruct B { a: A, b: byte }").unwrap_err(),
+        DecodeError::ZeroSize("A".to_string())
+    );
+    assert_eq!(
+        Schema::parse("array A [byte; 2];\narray A [byte; 3];").unwrap_err(),
+        DecodeError::DuplicateType("A".to_string())
+    );
+    assert_eq!(
+        Schema::parse("array A [byte; 2];\n\noption B A;").unwrap_err(),
+        DecodeError::Syntax {
+            line: 3,
+            reason: "expected ("
+        }
+    );
+}
+
+#[test]
+fn test_json_building_packet_round_trip() {
+    let witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    let script_info = type_demo_script_info(&tx)
+        .as_builder()
+        // not UTF-8
+        .name(
+            mol_string("spore")
+                .as_builder()
+                .push(Byte::new(0xff))
+                .build(),
+        )
+        .schema(mol_string(include_str!("../../schemas/spore.mol")))
+        .message_type(mol_string("SporeAction"))
+        .build();
+    let packet = new_packet_builder(&tx, &resolved_inputs)
+        .action(script_info, b"MINT")
+        .change_output(1)
+        .build()
+        .unwrap();
+
+    let text = serde_json::to_string(&json::BuildingPacket::from(packet.clone())).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(value["type"], "BuildingPacketV1");
+    assert_eq!(value["value"]["change_output"], "0x1");
+    assert_eq!(
+        value["value"]["message"]["actions"][0]["data"],
+        "0x4d494e54"
+    );
+    assert_eq!(value["value"]["script_infos"][0]["name"], "0x73706f7265ff");
+
+    let decoded: json::BuildingPacket = serde_json::from_str(&text).unwrap();
+    let decoded = ckb_transaction_cobuild::schemas::basic::BuildingPacket::from(decoded);
+    assert_eq!(decoded.as_slice(), packet.as_slice());
+}
+
+#[test]
+fn test_json_witness_layout_round_trip() {
+    let message = Message::new_builder()
+        .actions(
+            ActionVec::new_builder()
+                .push(
+                    Action::new_builder()
+                        .data(b"MINT".as_slice().pack())
+                        .build(),
+                )
+                .build(),
+        )
+        .build();
+    let seal = SealPair::new_builder()
+        .script_hash([1u8; 32].pack())
+        .seal([2u8; 65].as_slice().pack())
+        .build();
+    let layouts = [
+        WitnessLayoutUnion::SighashAll(
+            SighashAll::new_builder()
+                .message(message.clone())
+                .seal([3u8; 65].as_slice().pack())
+                .build(),
+        ),
+        WitnessLayoutUnion::SighashAllOnly(
+            SighashAllOnly::new_builder()
+                .seal([4u8; 65].as_slice().pack())
+                .build(),
+        ),
+        WitnessLayoutUnion::OtxStart(
+            OtxStart::new_builder()
+                .start_input_cell(1u32.pack())
+                .start_output_cell(2u32.pack())
+                .start_cell_deps(3u32.pack())
+                .start_header_deps(4u32.pack())
+                .build(),
+        ),
+        WitnessLayoutUnion::Otx(
+            Otx::new_builder()
+                .input_cells(1u32.pack())
+                .output_cells(1u32.pack())
+                .message(message)
+                .seals(SealPairVec::new_builder().push(seal).build())
+                .build(),
+        ),
+    ];
+    for layout in layouts {
+        let layout = WitnessLayout::new_builder().set(layout).build();
+        let text = serde_json::to_string(&json::WitnessLayout::from(layout.clone())).unwrap();
+        let decoded: json::WitnessLayout = serde_json::from_str(&text).unwrap();
+        assert_eq!(WitnessLayout::from(decoded).as_slice(), layout.as_slice());
+    }
+
+    let otx_start: json::WitnessLayout = serde_json::from_str(
+        r#"{"type":"OtxStart","value":{"start_input_cell":"0x1","start_output_cell":"0x2","start_cell_deps":"0x0","start_header_deps":"0x0"}}"#,
+    )
+    .unwrap();
+    let otx_start = WitnessLayout::from(otx_start);
+    assert!(matches!(
+        classify_witness(otx_start.as_slice()),
+        WitnessKind::OtxStart { .. }
+    ));
+    // unknown fields are rejected
+    assert!(serde_json::from_str::<json::SighashAllOnly>(r#"{"seal":"0x","lock":"0x"}"#).is_err());
+}
+
+#[test]
+fn test_tcob_inspect() {
+    let mut witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    witnesses.set_with_action(0);
+    let (tx, resolved_inputs, _context) = gen_tx(&witnesses);
+    let tx = sign_tx(&mut witnesses, tx, resolved_inputs);
+
+    let loaded = tcob::tx::parse_tx(&hex::encode(tx.data().as_slice())).unwrap();
+    assert!(loaded.resolved_inputs.is_none());
+    let text = tcob::inspect::inspect(&loaded);
+    assert!(text.contains("witness 0: SighashAll"));
+    assert!(text.contains("witness 1: empty"));
+    assert!(text.contains("witness 2: SighashAllOnly"));
+    assert!(text.contains("message: 2 action(s)"));
+    assert!(!text.contains("layout violations"));
+    assert!(text.contains("input cells are unknown"));
+
+    // mock transaction gives the resolved inputs, so the lock groups are listed
+    let loaded = tcob::tx::parse_tx(DAPP_TX_DUMP).unwrap();
+    assert_eq!(
+        loaded.resolved_inputs.as_ref().unwrap().outputs().len(),
+        loaded.tx.raw().inputs().len()
+    );
+    let text = tcob::inspect::inspect(&loaded);
+    assert!(text.contains("\n  lock 0x"));
+    assert!(text.contains("witnesses: 0 ("));
+}
+
+#[test]
+fn test_tcob_sign() {
+    let key = [0x11u8; 32];
+    let mut witnesses = MessageWitnesses::new(vec![2], vec![]);
+    let data = &mut witnesses.message_data[0];
+    data.privkey = Privkey::from_slice(&key);
+    data.pubkey_hash = blake2b_256(data.privkey.pubkey().unwrap().serialize())[..20]
+        .try_into()
+        .unwrap();
+    let (tx, resolved_inputs, context) = gen_tx(&witnesses);
+    let packet = new_packet_builder(&tx, &resolved_inputs)
+        .action(type_demo_script_info(&tx), b"MINT")
+        .build()
+        .unwrap();
+
+    let packet = tcob::sign::parse_packet(&hex::encode(packet.as_slice())).unwrap();
+    let privkey = tcob::sign::parse_privkey(&format!("0x{}\n", hex::encode(key))).unwrap();
+    let tx = tcob::sign::sign_packet(&packet, &privkey)
+        .unwrap()
+        .into_view();
+    let kinds = classify_witnesses(
+        &tx.witnesses()
+            .into_iter()
+            .map(|w| w.raw_data())
+            .collect::<Vec<_>>(),
+    );
+    assert!(matches!(kinds[0], WitnessKind::SighashAll { .. }));
+    assert_eq!(kinds[1], WitnessKind::Empty);
+    // run
+    let cycles = context
+        .verify_tx(&tx, MAX_CYCLES)
+        .expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // the key locks no input cell
+    let privkey = tcob::sign::parse_privkey(&hex::encode([0x22u8; 32])).unwrap();
+    assert!(tcob::sign::sign_packet(&packet, &privkey).is_err());
+}
+
+#[test]
+fn test_explain_signing_message_hash() {
+    let mut witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    witnesses.set_with_action(0);
+    witnesses.message_data[1].input_data = large_data(100);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    let tx = sign_tx(&mut witnesses, tx, resolved_inputs.clone());
+    let message = witnesses.get_action();
+
+    let trace = explain_signing_message_hash(&message, &tx.data(), &resolved_inputs).unwrap();
+    assert_eq!(
+        trace.digest,
+        signing_message_hash(&message, &tx.data(), &resolved_inputs).unwrap()
+    );
+    let names: Vec<&str> = trace.components.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "message",
+            "tx hash",
+            "input cell 0",
+            "input cell data length 0",
+            "input cell data 0",
+            "input cell 1",
+            "input cell data length 1",
+            "input cell data 1",
+            "input cell 2",
+            "input cell data length 2",
+            "input cell data 2",
+        ]
+    );
+    // the components are contiguous
+    let mut offset = 0;
+    for component in &trace.components {
+        assert_eq!(component.offset, offset);
+        offset += component.len;
+    }
+    let tx_hash_component = &trace.components[1];
+    assert_eq!(tx_hash_component.len, 32);
+    assert_eq!(tx_hash_component.hash, blake2b_256(tx.hash().as_slice()));
+    assert_eq!(trace.components[10].len, 100);
+
+    let loaded = tcob::tx::LoadedTx {
+        tx: tx.data(),
+        resolved_inputs: Some(resolved_inputs),
+    };
+    let text = tcob::digest::digest(&loaded, false).unwrap();
+    assert_eq!(
+        text,
+        format!("SighashAll: 0x{}\n", hex::encode(trace.digest))
+    );
+    let text = tcob::digest::digest(&loaded, true).unwrap();
+    assert!(text.contains("personalization ckb-tcob-sighash\n"));
+    assert!(text.contains(&trace.components[1].to_string()));
+}
+
+#[test]
+fn test_native_parse_message() {
+    let mut witnesses = MessageWitnesses::new(vec![2, 1], vec![]);
+    witnesses.set_with_action(0);
+    witnesses.message_data[1].input_data = large_data(100 * 1024 + 3);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    let tx = sign_tx(&mut witnesses, tx, resolved_inputs.clone());
+    let message = witnesses.get_action();
+    let expected = signing_message_hash(&message, &tx.data(), &resolved_inputs).unwrap();
+    let native = NativeTransaction::new(tx.data(), resolved_inputs.clone());
+
+    // the first lock group with SighashAll
+    let (digest, seal) = native.clone().run(|| parse_message().unwrap());
+    assert_eq!(digest, expected);
+    assert_eq!(&seal, witnesses.message_data[0].sign.as_ref().unwrap());
+    let fetched = native.clone().run(|| fetch_message().unwrap().unwrap());
+    assert_eq!(fetched.as_slice(), message.as_ref().unwrap().as_slice());
+
+    // the second lock group with SighashAllOnly, the input cell at 2
+    let lock_hash = blake2b_256(resolved_inputs.outputs().get(2).unwrap().lock().as_slice());
+    let native = native.lock_group(lock_hash);
+    let (digest, seal) = native.clone().run(|| parse_message().unwrap());
+    assert_eq!(digest, expected);
+    assert_eq!(&seal, witnesses.message_data[1].sign.as_ref().unwrap());
+    let verify = |seal: &[u8], digest: &[u8; 32]| {
+        digest == &expected && seal == witnesses.message_data[1].sign.as_ref().unwrap().as_slice()
+    };
+    assert_eq!(native.clone().run(|| cobuild_entry(verify)), Ok(true));
+    let reject = |_: &[u8], _: &[u8; 32]| false;
+    assert_eq!(
+        native.run(|| cobuild_entry(reject)),
+        Err(CobuildError::AuthError)
+    );
+}
+
+#[test]
+fn test_native_otx_message() {
+    let witnesses = MessageWitnesses::new(vec![1, 1, 1], vec![]);
+    let (tx, resolved_inputs, _) = gen_tx(&witnesses);
+    let otx_start = OtxStart::new_builder()
+        .start_input_cell(1u32.pack())
+        .start_output_cell(0u32.pack())
+        .start_cell_deps(0u32.pack())
+        .start_header_deps(0u32.pack())
+        .build();
+    let lock_hash = blake2b_256(resolved_inputs.outputs().get(1).unwrap().lock().as_slice());
+    let message = Message::new_builder()
+        .actions(
+            ActionVec::new_builder()
+                .push(
+                    Action::new_builder()
+                        .data([1u8, 2, 3].as_slice().pack())
+                        .build(),
+                )
+                .build(),
+        )
+        .build();
+    let otx = |input_cells: u32, output_cells: u32, cell_deps: u32| {
+        Otx::new_builder()
+            .input_cells(input_cells.pack())
+            .output_cells(output_cells.pack())
+            .cell_deps(cell_deps.pack())
+            .header_deps(0u32.pack())
+            .message(message.clone())
+            .seals(
+                SealPairVec::new_builder()
+                    .push(
+                        SealPair::new_builder()
+                            .script_hash(lock_hash.pack())
+                            .seal([7u8; 65].as_slice().pack())
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build()
+    };
+    let layouts = [
+        WitnessLayoutUnion::OtxStart(otx_start),
+        WitnessLayoutUnion::Otx(otx(1, 1, 1)),
+        WitnessLayoutUnion::Otx(otx(1, 1, 0)),
+    ];
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(
+            layouts
+                .into_iter()
+                .map(|layout| {
+                    WitnessLayout::new_builder()
+                        .set(layout)
+                        .build()
+                        .as_bytes()
+                        .pack()
+                })
+                .collect(),
+        )
+        .build();
+
+    let native = NativeTransaction::new(tx.data(), resolved_inputs.clone()).lock_group(lock_hash);
+    let batch = native.clone().run(|| fetch_otx_batch().unwrap());
+    assert_eq!(
+        batch,
+        OtxBatch {
+            start_witness: 0,
+            otx_count: 2,
+            ranges: OtxRanges {
+                input_cells: 1..3,
+                output_cells: 0..2,
+                cell_deps: 0..1,
+                header_deps: 0..0,
+            },
+        }
+    );
+
+    // only the first otx covers the input cell locked by the lock at 1
+    let items: Vec<_> = native.run(|| {
+        parse_otx_message(lock_hash)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    });
+    assert_eq!(items.len(), 1);
+    let expected = otx_signing_message_hash(
+        &message,
+        &tx.data(),
+        &resolved_inputs,
+        &OtxRanges {
+            input_cells: 1..2,
+            output_cells: 0..1,
+            cell_deps: 0..1,
+            header_deps: 0..0,
+        },
+    )
+    .unwrap();
+    assert_eq!(items[0].0, expected);
+    assert_eq!(items[0].1.len(), 1);
+}
+
+const SIGNING_DIGEST_VECTORS: &str = include_str!("../../test-vectors/signing-digests.json");
+
+#[test]
+fn test_signing_digest_vectors() {
+    let corpus: tcob::vectors::Vectors = serde_json::from_str(SIGNING_DIGEST_VECTORS).unwrap();
+    // the corpus checked in is the one produced by `tcob vectors`
+    assert_eq!(corpus, tcob::vectors::generate());
+    tcob::vectors::check(&corpus).unwrap();
+
+    // scripts calculate the same digests
+    let to_hex = |digest: [u8; 32]| format!("0x{}", hex::encode(digest));
+    for v in &corpus.vectors {
+        let tx = Transaction::from_slice(&hex::decode(&v.tx[2..]).unwrap()).unwrap();
+        let resolved_inputs =
+            ResolvedInputs::from_slice(&hex::decode(&v.resolved_inputs[2..]).unwrap()).unwrap();
+        let native = NativeTransaction::new(tx, resolved_inputs.clone());
+
+        // the first input cell is signed with SighashAll or SighashAllOnly
+        let (digest, _) = native.clone().run(parse_message).unwrap();
+        let expected = v.sighash_all.as_ref().unwrap_or(&v.sighash_all_only);
+        assert_eq!(&to_hex(digest), expected, "vector {}", v.name);
+
+        for otx in &v.otxs {
+            let lock = resolved_inputs
+                .outputs()
+                .get(otx.input_cells[0])
+                .unwrap()
+                .lock();
+            let script_hash = blake2b_256(lock.as_slice());
+            let digests: Vec<String> = native.clone().lock_group(script_hash).run(|| {
+                parse_otx_message(script_hash)
+                    .unwrap()
+                    .map(|item| to_hex(item.unwrap().0))
+                    .collect()
+            });
+            assert_eq!(digests, [otx.digest.clone()], "vector {}", v.name);
+        }
+    }
+}
+
+// a context with the lock demo and type demo deployed, returns the builder
+// with the cell deps of auth, the out point of the lock demo and the type demo
+fn testkit_builder() -> (CobuildTxBuilder, OutPoint, Script) {
+    let mut context = Context::default();
+    let loader = Loader::default();
+    let lock_out_point = context.deploy_cell(loader.load_binary("transaction-cobuild-lock-demo"));
+    let type_out_point = context.deploy_cell(loader.load_binary("transaction-cobuild-type-demo"));
+    let auth_out_point = context.deploy_cell(loader.load_binary("../auth"));
+    let secp256k1_out_point = context.deploy_cell(loader.load_binary("../secp256k1_data_20210801"));
+    let type_script = context
+        .build_script(&type_out_point, Bytes::new())
+        .expect("script");
+    let builder = CobuildTxBuilder::new(context)
+        .cell_dep(auth_out_point, DepType::Code)
+        .cell_dep(secp256k1_out_point, DepType::Code);
+    (builder, lock_out_point, type_script)
+}
+
+fn testkit_lock(
+    builder: &mut CobuildTxBuilder,
+    lock_out_point: &OutPoint,
+    key: &TestKey,
+) -> Script {
+    builder
+        .context_mut()
+        .build_script(lock_out_point, key.args())
+        .expect("script")
+}
+
+fn testkit_output(lock: &Script, type_script: Option<Script>) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(lock.clone())
+        .type_(type_script.pack())
+        .build()
+}
+
+#[test]
+fn test_testkit_sighash_all() {
+    let (mut builder, lock_out_point, type_script) = testkit_builder();
+    let keys = [TestKey::random(), TestKey::random()];
+    let locks: Vec<_> = keys
+        .iter()
+        .map(|key| testkit_lock(&mut builder, &lock_out_point, key))
+        .collect();
+    let tx = builder
+        .lock_group(locks[0].clone(), keys[0].clone())
+        .lock_group(locks[1].clone(), keys[1].clone())
+        .input(0, Bytes::new())
+        .input(1, Bytes::from(vec![1]))
+        .input(0, Bytes::from(vec![2; 10]))
+        .output(testkit_output(&locks[0], None), Bytes::new())
+        .output(
+            testkit_output(&locks[1], Some(type_script.clone())),
+            Bytes::from("minted"),
+        )
+        .action(type_script.calc_script_hash(), Bytes::from("MINT"))
+        .extra_witness(Bytes::from(vec![3; 20]))
+        .build();
+    let cycles = tx.verify(MAX_CYCLES).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // the type demo rejects an action not matching the cells
+    let (mut builder, lock_out_point, type_script) = testkit_builder();
+    let lock = testkit_lock(&mut builder, &lock_out_point, &keys[0]);
+    let tx = builder
+        .lock_group(lock.clone(), keys[0].clone())
+        .input(0, Bytes::new())
+        .output(
+            testkit_output(&lock, Some(type_script.clone())),
+            Bytes::new(),
+        )
+        .action(type_script.calc_script_hash(), Bytes::from("MINT"))
+        .build();
+    tx.assert_error(MAX_CYCLES, 5); // return Error::InvalidMessage of type demo
+
+    // a seal signed by another key
+    let (mut builder, lock_out_point, _) = testkit_builder();
+    let lock = testkit_lock(&mut builder, &lock_out_point, &keys[0]);
+    let tx = builder
+        .lock_group(lock.clone(), keys[1].clone())
+        .input(0, Bytes::new())
+        .output(testkit_output(&lock, None), Bytes::new())
+        .build();
+    tx.assert_error(MAX_CYCLES, 5); // return Error::AuthError
+}
+
+#[test]
+fn test_testkit_otx() {
+    let (mut builder, lock_out_point, type_script) = testkit_builder();
+    let keys = [TestKey::random(), TestKey::random(), TestKey::random()];
+    let locks: Vec<_> = keys
+        .iter()
+        .map(|key| testkit_lock(&mut builder, &lock_out_point, key))
+        .collect();
+    let builder = keys
+        .iter()
+        .zip(&locks)
+        .fold(builder, |builder, (key, lock)| {
+            builder.lock_group(lock.clone(), key.clone())
+        });
+    let tx = builder
+        .input(0, Bytes::new())
+        .output(testkit_output(&locks[0], None), Bytes::new())
+        .otx(
+            OtxBuilder::new()
+                .input(1, Bytes::new())
+                .input(2, Bytes::from(vec![1]))
+                .output(testkit_output(&locks[1], None), Bytes::new()),
+        )
+        .otx(
+            OtxBuilder::new()
+                .input(1, Bytes::new())
+                .output(
+                    testkit_output(&locks[1], Some(type_script.clone())),
+                    Bytes::from("minted"),
+                )
+                .action(type_script.calc_script_hash(), Bytes::from("MINT")),
+        )
+        .build();
+    let cycles = tx.verify(MAX_CYCLES).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+
+    // witnesses: the seal of lock group 0, OtxStart and two Otx
+    let batch = NativeTransaction::new(tx.tx.data(), tx.resolved_inputs.clone())
+        .run(fetch_otx_batch)
+        .unwrap();
+    assert_eq!(batch.start_witness, 1);
+    assert_eq!(batch.otx_count, 2);
+    assert_eq!(batch.ranges.input_cells, 1..4);
+    assert_eq!(batch.ranges.output_cells, 1..3);
 
-    let mut result = [0u8; 32];
-    hasher.finalize(&mut result);
-    result
+    // the input cell out of otx must be sealed
+    let err = tx
+        .verify_with_witness(0, Bytes::new(), MAX_CYCLES)
+        .expect_err("no seal");
+    assert_script_error(err, 4); // return Error::Encoding
 }